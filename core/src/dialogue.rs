@@ -0,0 +1,316 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::flags::Flags;
+use crate::level::Level;
+use crate::parse::ParseError;
+use crate::puzzle::PuzzleID;
+use crate::world::Room;
+
+// the dialogue the game ships with, also used when no dialogue file is given
+pub const DEFAULT_DIALOGUE: &str = include_str!("../../resources/dialogue.txt");
+
+pub const MAX_CHOICES: usize = 4;
+
+// what starts a conversation
+#[derive(Clone, PartialEq, Debug)]
+pub enum Trigger {
+    Enter(Room),
+    // the index of the interactable in the room's entries
+    Interact(Room, usize),
+    // the name of the examine spot in the level
+    Examine(String),
+    Solve(PuzzleID),
+    // a choice setting the flag, which goes straight on to the conversation
+    Flag(String),
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Choice {
+    pub flag: String,
+    pub text: String,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Line {
+    pub speaker: String,
+    pub text: String,
+    // picked from once the line has been typed out
+    pub choices: Vec<Choice>,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Conversation {
+    pub name: String,
+    pub triggers: Vec<Trigger>,
+    // (flag, whether it has to be set or not) for the conversation to be had
+    pub conditions: Vec<(String, bool)>,
+    // flags set once the conversation is had
+    pub sets: Vec<String>,
+    // had every time it is triggered instead of only once
+    pub repeat: bool,
+    pub lines: Vec<Line>,
+}
+
+// where the choice is drawn and can be clicked, as [x, y, w, h]
+pub fn get_choice_rect(idx: usize) -> [f32; 4] {
+    [200f32, 190f32 + idx as f32 * 50f32, 400f32, 40f32]
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Dialogue {
+    conversations: Vec<Conversation>,
+    // speaker name to the image drawn next to what they say
+    portraits: HashMap<String, Option<String>>,
+}
+
+impl Default for Dialogue {
+    fn default() -> Self {
+        Dialogue::parse(DEFAULT_DIALOGUE).unwrap_or_else(|_| Dialogue {
+            conversations: Vec::new(),
+            portraits: HashMap::new(),
+        })
+    }
+}
+
+fn parse_trigger(words: &[&str]) -> Result<Trigger, String> {
+    let room = |word: Option<&&str>| match word.and_then(|word| Room::from_name(word)) {
+        Some(room) => Ok(room),
+        None => Err(format!("invalid room \"{}\"", word.unwrap_or(&""))),
+    };
+    match (words.get(1), words.len()) {
+        (Some(&"enter"), 3) => Ok(Trigger::Enter(room(words.get(2))?)),
+        (Some(&"interact"), 4) => match words[3].parse::<usize>() {
+            Ok(idx) => Ok(Trigger::Interact(room(words.get(2))?, idx)),
+            Err(_) => Err(format!("invalid interactable index \"{}\"", words[3])),
+        },
+        (Some(&"examine"), 3) => Ok(Trigger::Examine(words[2].to_string())),
+        (Some(&"solve"), 3) => match PuzzleID::from_name(words[2]) {
+            Some(id) => Ok(Trigger::Solve(id)),
+            None => Err(format!("invalid puzzle id \"{}\"", words[2])),
+        },
+        (Some(&"flag"), 3) => Ok(Trigger::Flag(words[2].to_string())),
+        _ => Err(format!("invalid trigger \"{}\"", words.join(" "))),
+    }
+}
+
+impl Dialogue {
+    // Every error in the file is collected, like with the level
+    pub fn parse(contents: &str) -> Result<Dialogue, Vec<ParseError>> {
+        let mut conversations: Vec<Conversation> = Vec::new();
+        let mut portraits = HashMap::new();
+        let mut errors = Vec::new();
+
+        for (idx, line) in contents.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let words: Vec<&str> = line.split_whitespace().collect();
+            let result = match (words[0], conversations.last_mut()) {
+                ("speaker", _) => match words.len() {
+                    2 | 3 => {
+                        portraits.insert(
+                            words[1].to_string(),
+                            words.get(2).map(|image| image.to_string()),
+                        );
+                        Ok(())
+                    }
+                    _ => Err(format!("invalid speaker \"{}\"", line)),
+                },
+                ("conversation", _) => match words.get(1) {
+                    Some(name) if words.len() == 2 => {
+                        if conversations.iter().any(|c| c.name == *name) {
+                            Err(format!("conversation {} is given twice", name))
+                        } else {
+                            conversations.push(Conversation {
+                                name: name.to_string(),
+                                triggers: Vec::new(),
+                                conditions: Vec::new(),
+                                sets: Vec::new(),
+                                repeat: false,
+                                lines: Vec::new(),
+                            });
+                            Ok(())
+                        }
+                    }
+                    _ => Err(format!("invalid conversation \"{}\"", line)),
+                },
+                (_, None) => Err(format!("\"{}\" is not inside a conversation", line)),
+                ("on", Some(conversation)) => {
+                    parse_trigger(&words).map(|trigger| conversation.triggers.push(trigger))
+                }
+                ("if", Some(conversation)) | ("unless", Some(conversation)) => {
+                    if words.len() == 2 {
+                        let is_set = words[0] == "if";
+                        conversation.conditions.push((words[1].to_string(), is_set));
+                        Ok(())
+                    } else {
+                        Err(format!("invalid condition \"{}\"", line))
+                    }
+                }
+                ("set", Some(conversation)) => {
+                    if words.len() == 2 {
+                        conversation.sets.push(words[1].to_string());
+                        Ok(())
+                    } else {
+                        Err(format!("invalid set \"{}\"", line))
+                    }
+                }
+                ("repeat", Some(conversation)) => {
+                    if words.len() == 1 {
+                        conversation.repeat = true;
+                        Ok(())
+                    } else {
+                        Err(format!("invalid repeat \"{}\"", line))
+                    }
+                }
+                ("line", Some(conversation)) => match words.get(1) {
+                    Some(speaker) if words.len() > 2 => {
+                        if portraits.contains_key(*speaker) {
+                            let text = line["line".len()..].trim()[speaker.len()..].trim();
+                            conversation.lines.push(Line {
+                                speaker: speaker.to_string(),
+                                text: text.to_string(),
+                                choices: Vec::new(),
+                            });
+                            Ok(())
+                        } else {
+                            Err(format!("speaker {} is not given", speaker))
+                        }
+                    }
+                    _ => Err(format!("invalid line \"{}\"", line)),
+                },
+                ("choice", Some(conversation)) => {
+                    match (words.get(1), conversation.lines.last_mut()) {
+                        (Some(flag), Some(last)) if words.len() > 2 => {
+                            if last.choices.len() < MAX_CHOICES {
+                                let text = line["choice".len()..].trim()[flag.len()..].trim();
+                                last.choices.push(Choice {
+                                    flag: flag.to_string(),
+                                    text: text.to_string(),
+                                });
+                                Ok(())
+                            } else {
+                                Err(format!("a line has at most {} choices", MAX_CHOICES))
+                            }
+                        }
+                        (_, None) => Err(String::from("a choice has to come after a line")),
+                        _ => Err(format!("invalid choice \"{}\"", line)),
+                    }
+                }
+                _ => Err(format!("unknown entry \"{}\"", words[0])),
+            };
+            if let Err(message) = result {
+                errors.push(ParseError {
+                    line: idx + 1,
+                    message,
+                });
+            }
+        }
+
+        let line = contents.lines().count();
+        for conversation in &conversations {
+            if conversation.lines.is_empty() {
+                errors.push(ParseError {
+                    line,
+                    message: format!("conversation {} has no lines", conversation.name),
+                });
+            }
+            if conversation
+                .lines
+                .iter()
+                .any(|line| line.choices.len() == 1)
+            {
+                errors.push(ParseError {
+                    line,
+                    message: format!(
+                        "conversation {} has a line with only one choice",
+                        conversation.name
+                    ),
+                });
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(Dialogue {
+                conversations,
+                portraits,
+            })
+        } else {
+            Err(errors)
+        }
+    }
+
+    pub fn get_conversation(&self, idx: usize) -> Option<&Conversation> {
+        self.conversations.get(idx)
+    }
+
+    // the first conversation started by the trigger that hasn't been played,
+    // unless it repeats, and whose flags are as it needs them
+    pub fn find(
+        &self,
+        trigger: &Trigger,
+        played: &HashSet<String>,
+        flags: &Flags,
+    ) -> Option<usize> {
+        self.conversations.iter().position(|conversation| {
+            conversation.triggers.contains(trigger)
+                && (conversation.repeat || !played.contains(&conversation.name))
+                && conversation
+                    .conditions
+                    .iter()
+                    .all(|(flag, is_set)| flags.is_set(flag) == *is_set)
+        })
+    }
+
+    pub fn get_portrait(&self, speaker: &str) -> Option<&str> {
+        self.portraits.get(speaker)?.as_deref()
+    }
+
+    // every image used for a portrait
+    pub fn get_portraits(&self) -> Vec<&str> {
+        let mut images: Vec<&str> = self
+            .portraits
+            .values()
+            .flatten()
+            .map(|image| image.as_str())
+            .collect();
+        images.sort_unstable();
+        images.dedup();
+        images
+    }
+
+    // triggers of things to use or examine that the level doesn't have
+    pub fn get_missing_triggers(&self, level: &Level) -> Vec<String> {
+        let mut missing = Vec::new();
+        for conversation in &self.conversations {
+            for trigger in &conversation.triggers {
+                match trigger {
+                    Trigger::Interact(room, idx) => {
+                        let count = level
+                            .get_room(*room)
+                            .map(|layout| layout.interactables.len())
+                            .unwrap_or(0);
+                        if *idx >= count {
+                            missing.push(format!(
+                                "{} is had on using {} {}, which has {} things to use",
+                                conversation.name,
+                                room.get_name(),
+                                idx,
+                                count
+                            ));
+                        }
+                    }
+                    Trigger::Examine(name) if level.find_examine(name).is_none() => {
+                        missing.push(format!(
+                            "{} is had on examining {}, which the level doesn't have",
+                            conversation.name, name
+                        ));
+                    }
+                    _ => (),
+                }
+            }
+        }
+        missing
+    }
+}
@@ -0,0 +1,63 @@
+const DEFAULT_RADIUS: f32 = 70f32;
+
+pub const DOOR_WIDTH: f32 = 96f32;
+pub const DOOR_HEIGHT: f32 = 160f32;
+
+pub struct Door {
+    is_open: bool,
+    x: f32,
+    y: f32,
+    id: usize,
+    enter_radius: f32,
+}
+
+impl Door {
+    pub fn new(is_open: bool, x: f32, y: f32, id: usize) -> Self {
+        Door {
+            is_open,
+            x,
+            y,
+            id,
+            enter_radius: DEFAULT_RADIUS,
+        }
+    }
+
+    pub fn get_open(&self) -> bool {
+        self.is_open
+    }
+
+    pub fn set_open(&mut self, is_open: bool) {
+        self.is_open = is_open;
+    }
+
+    pub fn toggle_open(&mut self) -> bool {
+        self.is_open = !self.is_open;
+        self.is_open
+    }
+
+    pub fn get_id(&self) -> usize {
+        self.id
+    }
+
+    pub fn is_within_range(&self, x: f32, y: f32) -> bool {
+        let a = (self.x + DOOR_WIDTH / 2f32) - x;
+        let b = (self.y + 80f32) - y;
+        (a * a + b * b).sqrt() <= self.enter_radius
+    }
+
+    pub fn get_x(&self) -> f32 {
+        self.x
+    }
+
+    pub fn get_y(&self) -> f32 {
+        self.y
+    }
+
+    pub fn get_center_x(&self) -> f32 {
+        self.x + DOOR_WIDTH / 2f32
+    }
+
+    pub fn get_center_y(&self) -> f32 {
+        self.y + 80f32
+    }
+}
@@ -0,0 +1,26 @@
+use std::collections::BTreeSet;
+
+// Named story flags, set by the choices made in conversations and kept in the
+// save
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct Flags {
+    names: BTreeSet<String>,
+}
+
+impl Flags {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn set(&mut self, name: &str) {
+        self.names.insert(name.to_string());
+    }
+
+    pub fn is_set(&self, name: &str) -> bool {
+        self.names.contains(name)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &str> {
+        self.names.iter().map(|name| name.as_str())
+    }
+}
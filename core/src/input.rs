@@ -0,0 +1,54 @@
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub enum Action {
+    Left,
+    Right,
+    Up,
+    Down,
+    Interact,
+    EnterDoor,
+    Back,
+}
+
+impl Action {
+    pub const ALL: [Action; 7] = [
+        Action::Left,
+        Action::Right,
+        Action::Up,
+        Action::Down,
+        Action::Interact,
+        Action::EnterDoor,
+        Action::Back,
+    ];
+
+    pub fn get_name(self) -> &'static str {
+        match self {
+            Action::Left => "Left",
+            Action::Right => "Right",
+            Action::Up => "Up",
+            Action::Down => "Down",
+            Action::Interact => "Interact",
+            Action::EnterDoor => "EnterDoor",
+            Action::Back => "Back",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Action> {
+        match name {
+            "Left" => Some(Action::Left),
+            "Right" => Some(Action::Right),
+            "Up" => Some(Action::Up),
+            "Down" => Some(Action::Down),
+            "Interact" => Some(Action::Interact),
+            "EnterDoor" => Some(Action::EnterDoor),
+            "Back" => Some(Action::Back),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub enum MouseButton {
+    Left,
+    Right,
+    Other,
+}
@@ -0,0 +1,69 @@
+use crate::puzzle::PuzzleID;
+
+const DEFAULT_RADIUS: f32 = 70f32;
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum InteractableType {
+    Door(usize),
+    LockedDoor(usize, bool),
+    Puzzle(PuzzleID, bool),
+    Ship,
+    // something to look at, what is said about it is in the dialogue
+    Examine,
+}
+
+pub struct Interactable {
+    itype: InteractableType,
+    x: f32,
+    y: f32,
+    radius: f32,
+}
+
+impl Interactable {
+    pub fn new(itype: InteractableType, x: f32, y: f32) -> Self {
+        Self {
+            itype,
+            x,
+            y,
+            radius: DEFAULT_RADIUS,
+        }
+    }
+
+    pub fn is_within_range(&self, x: f32, y: f32) -> bool {
+        let a = self.x - x;
+        let b = self.y - y;
+        (a * a + b * b).sqrt() <= self.radius
+    }
+
+    pub fn get_type(&self) -> InteractableType {
+        self.itype
+    }
+
+    pub fn get_radius(&self) -> f32 {
+        self.radius
+    }
+
+    pub fn set_radius(&mut self, radius: f32) {
+        self.radius = radius;
+    }
+
+    pub fn get_x(&self) -> f32 {
+        self.x
+    }
+
+    pub fn get_y(&self) -> f32 {
+        self.y
+    }
+
+    pub fn set_unlocked(&mut self, unlocked: bool) {
+        if let InteractableType::LockedDoor(_, is_unlocked) = &mut self.itype {
+            *is_unlocked = unlocked;
+        }
+    }
+
+    pub fn set_puzzle_cleared(&mut self, cleared: bool) {
+        if let InteractableType::Puzzle(_, is_cleared) = &mut self.itype {
+            *is_cleared = cleared;
+        }
+    }
+}
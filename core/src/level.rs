@@ -0,0 +1,437 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::interactable::InteractableType;
+use crate::parse::ParseError;
+use crate::puzzle::{PuzzleID, PuzzleLayout, TILES};
+use crate::world::{DoorIDs, Room};
+
+// the level the game ships with, also used when no level file is given
+pub const DEFAULT_LEVEL: &str = include_str!("../../resources/level.txt");
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct DoorLayout {
+    pub x: f32,
+    pub is_open: bool,
+    // the room it leads to
+    pub to: Room,
+    // doors with an id remember being opened when the room is left
+    pub id: Option<DoorIDs>,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct InteractableLayout {
+    pub itype: InteractableType,
+    pub x: f32,
+    pub y: f32,
+    pub radius: Option<f32>,
+    // for locks, the door whose state decides if it is unlocked
+    pub door_id: Option<DoorIDs>,
+    // (flag, whether it has to be set or not) for it to be used
+    pub condition: Option<(String, bool)>,
+    // for examine spots, what the dialogue calls it
+    pub name: Option<String>,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct ExitLayout {
+    pub to: Room,
+    // only open once the escape is on
+    pub escape: bool,
+}
+
+// a sound from the asset manifest looped while in the room
+#[derive(Clone, PartialEq, Debug)]
+pub struct AmbientLayout {
+    pub sound: String,
+    pub volume: f32,
+}
+
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct RoomLayout {
+    // None leaves whatever text was showing before entering the room
+    pub text: Option<String>,
+    pub darkness_yoffset: f32,
+    // how many rooms right of the pod it is, which the bad news moves by when
+    // going from room to room
+    pub position: f32,
+    pub left: Option<ExitLayout>,
+    pub right: Option<ExitLayout>,
+    pub doors: Vec<DoorLayout>,
+    pub interactables: Vec<InteractableLayout>,
+    pub ambient: Vec<AmbientLayout>,
+    // used instead of the usual ambience once the escape is on, if given
+    pub end_game_ambient: Vec<AmbientLayout>,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Level {
+    rooms: HashMap<Room, RoomLayout>,
+    puzzles: HashMap<PuzzleID, PuzzleLayout>,
+}
+
+impl Default for Level {
+    fn default() -> Self {
+        Level::parse(DEFAULT_LEVEL).unwrap_or_else(|_| Level {
+            rooms: HashMap::new(),
+            puzzles: HashMap::new(),
+        })
+    }
+}
+
+impl Level {
+    // Every error in the file is collected, so they can be fixed in one go
+    pub fn parse(contents: &str) -> Result<Level, Vec<ParseError>> {
+        let mut rooms = HashMap::new();
+        let mut puzzles = HashMap::new();
+        let mut examine_names = HashSet::new();
+        let mut errors = Vec::new();
+        let mut current: Option<(Room, RoomLayout)> = None;
+
+        for (idx, line) in contents.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let words: Vec<&str> = line.split_whitespace().collect();
+            let result = match (words[0], current.as_mut()) {
+                ("room", _) => match words.get(1).and_then(|name| Room::from_name(name)) {
+                    Some(room) if words.len() == 2 => {
+                        if let Some((room, layout)) = current.take() {
+                            rooms.insert(room, layout);
+                        }
+                        if rooms.contains_key(&room) {
+                            Err(format!("room {} is given twice", room.get_name()))
+                        } else {
+                            current = Some((room, RoomLayout::default()));
+                            Ok(())
+                        }
+                    }
+                    _ => Err(format!("invalid room \"{}\"", line)),
+                },
+                // puzzles are not part of a room, so they can go anywhere
+                ("puzzle_tiles", _) => parse_puzzle_tiles(&words).and_then(|(id, layout)| {
+                    match puzzles.insert(id, layout) {
+                        Some(_) => Err(format!("the tiles of {} are given twice", id.get_name())),
+                        None => Ok(()),
+                    }
+                }),
+                (_, None) => Err(format!("\"{}\" is not inside a room", line)),
+                ("text", Some((_, layout))) => {
+                    layout.text = Some(line["text".len()..].trim().to_string());
+                    Ok(())
+                }
+                // examine spots are found by name from any room
+                (_, Some((_, layout))) => {
+                    parse_entry(&words, layout).and_then(|_| match (words[0], words.get(1)) {
+                        ("examine", Some(name)) if !examine_names.insert(name.to_string()) => {
+                            Err(format!("examine spot {} is given twice", name))
+                        }
+                        _ => Ok(()),
+                    })
+                }
+            };
+            if let Err(message) = result {
+                errors.push(ParseError {
+                    line: idx + 1,
+                    message,
+                });
+            }
+        }
+        if let Some((room, layout)) = current.take() {
+            rooms.insert(room, layout);
+        }
+
+        let line = contents.lines().count();
+        for room in Room::ALL.iter() {
+            if !rooms.contains_key(room) {
+                errors.push(ParseError {
+                    line,
+                    message: format!("room {} is missing", room.get_name()),
+                });
+            }
+        }
+        for id in PuzzleID::ALL.iter() {
+            if !puzzles.contains_key(id) {
+                errors.push(ParseError {
+                    line,
+                    message: format!("the tiles of {} are missing", id.get_name()),
+                });
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(Level { rooms, puzzles })
+        } else {
+            Err(errors)
+        }
+    }
+
+    pub fn get_room(&self, room: Room) -> Option<&RoomLayout> {
+        self.rooms.get(&room)
+    }
+
+    // the room of the examine spot and its index among the room's entries
+    pub fn find_examine(&self, name: &str) -> Option<(Room, usize)> {
+        self.rooms.iter().find_map(|(room, layout)| {
+            layout
+                .interactables
+                .iter()
+                .position(|interactable| interactable.name.as_deref() == Some(name))
+                .map(|idx| (*room, idx))
+        })
+    }
+
+    pub fn get_puzzle(&self, id: PuzzleID) -> Option<&PuzzleLayout> {
+        self.puzzles.get(&id)
+    }
+
+    // every sound used for ambience in any room
+    pub fn get_ambient_sounds(&self) -> Vec<&str> {
+        let mut sounds: Vec<&str> = self
+            .rooms
+            .values()
+            .flat_map(|layout| layout.ambient.iter().chain(&layout.end_game_ambient))
+            .map(|ambient| ambient.sound.as_str())
+            .collect();
+        sounds.sort_unstable();
+        sounds.dedup();
+        sounds
+    }
+}
+
+fn parse_number(word: Option<&&str>) -> Result<f32, String> {
+    match word.map(|word| word.parse::<f32>()) {
+        Some(Ok(number)) => Ok(number),
+        Some(Err(_)) => Err(format!("\"{}\" is not a number", word.unwrap_or(&""))),
+        None => Err(String::from("missing a number")),
+    }
+}
+
+fn parse_door_index(word: Option<&&str>, layout: &RoomLayout) -> Result<usize, String> {
+    match word.map(|word| word.parse::<usize>()) {
+        Some(Ok(idx)) if idx < layout.doors.len() => Ok(idx),
+        Some(Ok(idx)) => Err(format!("there is no door {} in this room", idx)),
+        _ => Err(String::from("missing a door index")),
+    }
+}
+
+fn parse_room(word: &str) -> Result<Room, String> {
+    Room::from_name(word).ok_or_else(|| format!("invalid room \"{}\"", word))
+}
+
+fn parse_door_id(word: Option<&&str>) -> Result<DoorIDs, String> {
+    match word.and_then(|word| DoorIDs::from_name(word)) {
+        Some(id) => Ok(id),
+        None => Err(format!("invalid door id \"{}\"", word.unwrap_or(&""))),
+    }
+}
+
+// puzzle_tiles <puzzle id> <start> <tiles turned over by each tile>, with the
+// start as a 1 or 0 for each tile and the tiles by their number
+fn parse_puzzle_tiles(words: &[&str]) -> Result<(PuzzleID, PuzzleLayout), String> {
+    check_length(words, 3 + TILES, 3 + TILES)?;
+    let id = match PuzzleID::from_name(words[1]) {
+        Some(id) => id,
+        None => return Err(format!("invalid puzzle id \"{}\"", words[1])),
+    };
+    let start: Vec<bool> = words[2]
+        .chars()
+        .filter_map(|c| match c {
+            '1' => Some(true),
+            '0' => Some(false),
+            _ => None,
+        })
+        .collect();
+    if start.len() != TILES || words[2].len() != TILES {
+        return Err(format!(
+            "the start is a 1 or 0 for each of the {} tiles, not \"{}\"",
+            TILES, words[2]
+        ));
+    }
+    let mut presses = Vec::new();
+    for word in &words[3..] {
+        let mut tiles = Vec::new();
+        for c in word.chars() {
+            match c.to_digit(10).map(|tile| tile as usize) {
+                Some(tile) if tile < TILES && !tiles.contains(&tile) => tiles.push(tile),
+                _ => return Err(format!("invalid tiles \"{}\"", word)),
+            }
+        }
+        presses.push(tiles);
+    }
+    Ok((id, PuzzleLayout { start, presses }))
+}
+
+fn check_length(words: &[&str], min: usize, max: usize) -> Result<(), String> {
+    if words.len() < min || words.len() > max {
+        Err(format!("invalid {} \"{}\"", words[0], words.join(" ")))
+    } else {
+        Ok(())
+    }
+}
+
+fn parse_entry(words: &[&str], layout: &mut RoomLayout) -> Result<(), String> {
+    // if <flag> or unless <flag> at the end of anything that can be used
+    let (words, condition) = match words {
+        [rest @ .., "if", flag] if rest.len() > 1 => (rest, Some((flag.to_string(), true))),
+        [rest @ .., "unless", flag] if rest.len() > 1 => (rest, Some((flag.to_string(), false))),
+        _ => (words, None),
+    };
+    let count = layout.interactables.len();
+    match words[0] {
+        "darkness" => {
+            check_length(words, 2, 2)?;
+            layout.darkness_yoffset = parse_number(words.get(1))?;
+        }
+        "position" => {
+            check_length(words, 2, 2)?;
+            layout.position = parse_number(words.get(1))?;
+        }
+        // exit <left|right> <room> [escape]
+        "exit" => {
+            check_length(words, 3, 4)?;
+            let exit = ExitLayout {
+                to: parse_room(words[2])?,
+                escape: match words.get(3) {
+                    Some(&"escape") => true,
+                    Some(other) => return Err(format!("invalid exit condition \"{}\"", other)),
+                    None => false,
+                },
+            };
+            let side = match words[1] {
+                "left" => &mut layout.left,
+                "right" => &mut layout.right,
+                other => return Err(format!("an exit is left or right, not \"{}\"", other)),
+            };
+            if side.is_some() {
+                return Err(format!("the {} exit is given twice", words[1]));
+            }
+            *side = Some(exit);
+        }
+        // door <x> <open|closed> <room> [door id]
+        "door" => {
+            check_length(words, 4, 5)?;
+            let x = parse_number(words.get(1))?;
+            let is_open = match words[2] {
+                "open" => true,
+                "closed" => false,
+                other => return Err(format!("a door is open or closed, not \"{}\"", other)),
+            };
+            let to = parse_room(words[3])?;
+            let id = match words.get(4) {
+                Some(_) => Some(parse_door_id(words.get(4))?),
+                None => None,
+            };
+            layout.doors.push(DoorLayout { x, is_open, to, id });
+        }
+        // door_switch <door index> <x> <y>
+        "door_switch" => {
+            check_length(words, 4, 4)?;
+            layout.interactables.push(InteractableLayout {
+                itype: InteractableType::Door(parse_door_index(words.get(1), layout)?),
+                x: parse_number(words.get(2))?,
+                y: parse_number(words.get(3))?,
+                radius: None,
+                door_id: None,
+                condition: None,
+                name: None,
+            });
+        }
+        // lock <door index> <x> <y> <door id> <locked|unlocked>, where the
+        // last word is used until the door id has a state
+        "lock" => {
+            check_length(words, 6, 6)?;
+            let idx = parse_door_index(words.get(1), layout)?;
+            let unlocked = match words[5] {
+                "unlocked" => true,
+                "locked" => false,
+                other => return Err(format!("a lock is locked or unlocked, not \"{}\"", other)),
+            };
+            layout.interactables.push(InteractableLayout {
+                itype: InteractableType::LockedDoor(idx, unlocked),
+                x: parse_number(words.get(2))?,
+                y: parse_number(words.get(3))?,
+                radius: None,
+                door_id: Some(parse_door_id(words.get(4))?),
+                condition: None,
+                name: None,
+            });
+        }
+        // puzzle <puzzle id> <x> <y> [radius]
+        "puzzle" => {
+            check_length(words, 4, 5)?;
+            let id = match PuzzleID::from_name(words[1]) {
+                Some(id) => id,
+                None => return Err(format!("invalid puzzle id \"{}\"", words[1])),
+            };
+            let radius = match words.get(4) {
+                Some(_) => Some(parse_number(words.get(4))?),
+                None => None,
+            };
+            layout.interactables.push(InteractableLayout {
+                itype: InteractableType::Puzzle(id, false),
+                x: parse_number(words.get(2))?,
+                y: parse_number(words.get(3))?,
+                radius,
+                door_id: None,
+                condition: None,
+                name: None,
+            });
+        }
+        // ship <x> <y>
+        "ship" => {
+            check_length(words, 3, 3)?;
+            layout.interactables.push(InteractableLayout {
+                itype: InteractableType::Ship,
+                x: parse_number(words.get(1))?,
+                y: parse_number(words.get(2))?,
+                radius: None,
+                door_id: None,
+                condition: None,
+                name: None,
+            });
+        }
+        // examine <name> <x> <y> [radius]
+        "examine" => {
+            check_length(words, 4, 5)?;
+            let radius = match words.get(4) {
+                Some(_) => Some(parse_number(words.get(4))?),
+                None => None,
+            };
+            layout.interactables.push(InteractableLayout {
+                itype: InteractableType::Examine,
+                x: parse_number(words.get(2))?,
+                y: parse_number(words.get(3))?,
+                radius,
+                door_id: None,
+                condition: None,
+                name: Some(words[1].to_string()),
+            });
+        }
+        // ambient <sound> <volume> and end_game_ambient <sound> <volume>
+        "ambient" | "end_game_ambient" => {
+            check_length(words, 3, 3)?;
+            let volume = parse_number(words.get(2))?;
+            if !(0f32..=1f32).contains(&volume) {
+                return Err(format!("a volume is from 0 to 1, not {}", volume));
+            }
+            let ambient = AmbientLayout {
+                sound: words[1].to_string(),
+                volume,
+            };
+            if words[0] == "ambient" {
+                layout.ambient.push(ambient);
+            } else {
+                layout.end_game_ambient.push(ambient);
+            }
+        }
+        other => return Err(format!("unknown entry \"{}\"", other)),
+    }
+    if let Some(condition) = condition {
+        match layout.interactables.get_mut(count) {
+            Some(interactable) => interactable.condition = Some(condition),
+            None => return Err(format!("{} can't have a condition", words[0])),
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,14 @@
+pub mod dialogue;
+pub mod door;
+pub mod flags;
+pub mod input;
+pub mod interactable;
+pub mod level;
+pub mod parse;
+pub mod player;
+pub mod puzzle;
+pub mod save;
+pub mod script;
+pub mod strings;
+pub mod typewriter;
+pub mod world;
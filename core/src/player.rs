@@ -0,0 +1,93 @@
+const WALK_TIME: f32 = 0.34f32;
+
+pub const PLAYER_WIDTH: f32 = 64f32;
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum PlayerState {
+    Standing,
+    Walking(bool, f32),
+}
+
+pub struct Player {
+    pub x: f32,
+    pub y: f32,
+    pub rot: f32,
+    pub opacity: f32,
+    state: PlayerState,
+    xflip: bool,
+    prev_x: f32,
+    prev_y: f32,
+    prev_rot: f32,
+}
+
+impl Player {
+    pub fn new() -> Self {
+        Self {
+            x: 300f32,
+            y: 300f32,
+            rot: 0f32,
+            opacity: 1f32,
+            state: PlayerState::Standing,
+            xflip: false,
+            prev_x: 300f32,
+            prev_y: 300f32,
+            prev_rot: 0f32,
+        }
+    }
+
+    pub fn set_walking(&mut self, is_walking: bool) {
+        if is_walking {
+            if self.state == PlayerState::Standing {
+                self.state = PlayerState::Walking(true, 0f32);
+            }
+        } else {
+            self.state = PlayerState::Standing;
+        }
+    }
+
+    pub fn get_state(&self) -> PlayerState {
+        self.state
+    }
+
+    pub fn set_xflip(&mut self, xflip: bool) {
+        self.xflip = xflip;
+    }
+
+    pub fn get_xflip(&self) -> bool {
+        self.xflip
+    }
+
+    // the point used to check if the player can reach doors and interactables
+    pub fn get_interaction_point(&self) -> (f32, f32) {
+        (self.x + 32f32, self.y + 64f32)
+    }
+
+    pub fn begin_step(&mut self) {
+        self.prev_x = self.x;
+        self.prev_y = self.y;
+        self.prev_rot = self.rot;
+    }
+
+    pub fn get_previous(&self) -> (f32, f32, f32) {
+        (self.prev_x, self.prev_y, self.prev_rot)
+    }
+
+    pub fn update(&mut self, dt: f32) {
+        match &mut self.state {
+            PlayerState::Standing => (),
+            PlayerState::Walking(ref mut left, ref mut timer) => {
+                *timer += dt;
+                if *timer >= WALK_TIME {
+                    *timer -= WALK_TIME;
+                    *left = !*left;
+                }
+            }
+        }
+    }
+}
+
+impl Default for Player {
+    fn default() -> Self {
+        Self::new()
+    }
+}
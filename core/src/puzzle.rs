@@ -0,0 +1,220 @@
+use crate::input::Action;
+
+// random presses used to scramble a seeded puzzle
+const SCRAMBLE_PRESSES: usize = 12;
+const SCRAMBLE_TRIES: usize = 100;
+// every puzzle is 3 by 3 tiles
+pub const TILES: usize = 9;
+
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub enum PuzzleID {
+    FarRightHall,
+    Computer,
+}
+
+impl PuzzleID {
+    pub const ALL: [PuzzleID; 2] = [PuzzleID::FarRightHall, PuzzleID::Computer];
+
+    pub fn get_name(self) -> &'static str {
+        match self {
+            PuzzleID::FarRightHall => "FarRightHall",
+            PuzzleID::Computer => "Computer",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<PuzzleID> {
+        PuzzleID::ALL
+            .iter()
+            .copied()
+            .find(|id| id.get_name() == name)
+    }
+}
+
+// How the tiles of a puzzle start out and what pressing each of them does,
+// given in the level
+#[derive(Clone, PartialEq, Debug)]
+pub struct PuzzleLayout {
+    pub start: Vec<bool>,
+    // for each tile, the tiles that pressing it turns over
+    pub presses: Vec<Vec<usize>>,
+}
+
+pub struct Puzzle {
+    ptype: PuzzleID,
+    layout: PuzzleLayout,
+    tiles: Vec<bool>,
+    key_pos: usize,
+    key_pressed: bool,
+    abort: bool,
+    force_solve: bool,
+    // what a reset goes back to, which is scrambled for a seeded puzzle
+    start: Vec<bool>,
+}
+
+impl Puzzle {
+    pub fn new(ptype: PuzzleID, layout: &PuzzleLayout) -> Self {
+        let mut puzzle = Self {
+            ptype,
+            layout: layout.clone(),
+            tiles: Vec::new(),
+            key_pos: 0,
+            key_pressed: true,
+            abort: false,
+            force_solve: false,
+            start: layout.start.clone(),
+        };
+
+        puzzle.reset();
+
+        puzzle
+    }
+
+    // Starts from random presses on a solved puzzle, so it can always be
+    // solved by pressing the same tiles again
+    pub fn with_seed(ptype: PuzzleID, layout: &PuzzleLayout, seed: u64) -> Self {
+        let mut puzzle = Self::new(ptype, layout);
+        let mut state = seed.wrapping_mul(0x9e37_79b9_7f4a_7c15) | 1;
+        puzzle.tiles = vec![true; TILES];
+        // a layout whose presses always cancel out keeps its usual start
+        for _ in 0..SCRAMBLE_TRIES {
+            for _ in 0..SCRAMBLE_PRESSES {
+                state ^= state << 13;
+                state ^= state >> 7;
+                state ^= state << 17;
+                puzzle.handle_puzzle_input((state % TILES as u64) as usize);
+            }
+            if !puzzle.is_solved() {
+                puzzle.start = puzzle.tiles.clone();
+                break;
+            }
+        }
+        puzzle.reset();
+        puzzle
+    }
+
+    pub fn reset(&mut self) {
+        self.tiles = self.start.clone();
+    }
+
+    pub fn skip(&mut self) {
+        self.force_solve = true;
+    }
+
+    pub fn get_type(&self) -> PuzzleID {
+        self.ptype
+    }
+
+    pub fn get_tiles(&self) -> &[bool] {
+        &self.tiles
+    }
+
+    // the keyboard cursor is only shown until the mouse is used
+    pub fn get_cursor(&self) -> Option<usize> {
+        if self.key_pressed {
+            Some(self.key_pos)
+        } else {
+            None
+        }
+    }
+
+    pub fn handle_click(&mut self, x: f32, y: f32) {
+        self.key_pressed = false;
+        match self.ptype {
+            PuzzleID::FarRightHall | PuzzleID::Computer => {
+                if y > 150f32 && y < 250f32 {
+                    if x > 250f32 && x < 350f32 {
+                        self.handle_puzzle_input(0);
+                    } else if x > 350f32 && x < 450f32 {
+                        self.handle_puzzle_input(1);
+                    } else if x > 450f32 && x < 550f32 {
+                        self.handle_puzzle_input(2);
+                    }
+                } else if y > 250f32 && y < 350f32 {
+                    if x > 250f32 && x < 350f32 {
+                        self.handle_puzzle_input(3);
+                    } else if x > 350f32 && x < 450f32 {
+                        self.handle_puzzle_input(4);
+                    } else if x > 450f32 && x < 550f32 {
+                        self.handle_puzzle_input(5);
+                    }
+                } else if y > 350f32 && y < 450f32 {
+                    if x > 250f32 && x < 350f32 {
+                        self.handle_puzzle_input(6);
+                    } else if x > 350f32 && x < 450f32 {
+                        self.handle_puzzle_input(7);
+                    } else if x > 450f32 && x < 550f32 {
+                        self.handle_puzzle_input(8);
+                    }
+                }
+            }
+        }
+    }
+
+    pub fn handle_action(&mut self, action: Action) {
+        match self.ptype {
+            PuzzleID::FarRightHall | PuzzleID::Computer => match action {
+                Action::Left => {
+                    let column = self.key_pos % 3;
+                    if column == 0 {
+                        self.key_pos += 2;
+                    } else {
+                        self.key_pos -= 1;
+                    }
+                    self.key_pressed = true;
+                }
+                Action::Right => {
+                    if self.key_pos % 3 == 2 {
+                        self.key_pos -= 2;
+                    } else {
+                        self.key_pos += 1;
+                    }
+                    self.key_pressed = true;
+                }
+                Action::Up => {
+                    if self.key_pos / 3 == 0 {
+                        self.key_pos += 6;
+                    } else {
+                        self.key_pos -= 3;
+                    }
+                    self.key_pressed = true;
+                }
+                Action::Down => {
+                    if self.key_pos / 3 == 2 {
+                        self.key_pos -= 6;
+                    } else {
+                        self.key_pos += 3;
+                    }
+                    self.key_pressed = true;
+                }
+                Action::Interact => {
+                    self.handle_puzzle_input(self.key_pos);
+                    self.key_pressed = true;
+                }
+                Action::Back => {
+                    self.abort = true;
+                }
+                Action::EnterDoor => (),
+            },
+        }
+    }
+
+    fn handle_puzzle_input(&mut self, idx: usize) {
+        let presses = match self.layout.presses.get(idx) {
+            Some(presses) => presses,
+            None => return,
+        };
+        for tile in presses {
+            if let Some(tile) = self.tiles.get_mut(*tile) {
+                *tile = !*tile;
+            }
+        }
+    }
+
+    pub fn is_solved(&self) -> bool {
+        self.force_solve || self.tiles.iter().all(|tile| *tile)
+    }
+
+    pub fn is_abort(&self) -> bool {
+        self.abort
+    }
+}
@@ -0,0 +1,48 @@
+use std::collections::BTreeSet;
+
+use crate::flags::Flags;
+use crate::parse::ParseError;
+
+// What is kept from one run to the next: the story flags and the conversations
+// that were had, so those that don't repeat aren't had again. Written out one
+// per line as "flag <name>" and "played <conversation>".
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct Save {
+    pub flags: Flags,
+    pub played: BTreeSet<String>,
+}
+
+impl Save {
+    pub fn to_text(&self) -> String {
+        let flags = self.flags.iter().map(|name| format!("flag {}\n", name));
+        let played = self.played.iter().map(|name| format!("played {}\n", name));
+        flags.chain(played).collect()
+    }
+
+    pub fn parse(contents: &str) -> Result<Save, Vec<ParseError>> {
+        let mut save = Save::default();
+        let mut errors = Vec::new();
+        for (idx, line) in contents.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let words: Vec<&str> = line.split_whitespace().collect();
+            match words[..] {
+                ["flag", name] => save.flags.set(name),
+                ["played", name] => {
+                    save.played.insert(name.to_string());
+                }
+                _ => errors.push(ParseError {
+                    line: idx + 1,
+                    message: format!("invalid entry \"{}\"", line),
+                }),
+            }
+        }
+        if errors.is_empty() {
+            Ok(save)
+        } else {
+            Err(errors)
+        }
+    }
+}
@@ -0,0 +1,113 @@
+use crate::strings::Strings;
+
+// The writing systems that may need a font of their own. Text is split into
+// runs of one script so each run can be drawn with a font that has it.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub enum Script {
+    // spaces, digits and punctuation, which go with the text around them
+    Common,
+    Latin,
+    Greek,
+    Cyrillic,
+    Hebrew,
+    Arabic,
+    Devanagari,
+    Thai,
+    Hangul,
+    // Chinese characters, kana and the full width forms used with them
+    Cjk,
+}
+
+impl Script {
+    pub const ALL: [Script; 10] = [
+        Script::Common,
+        Script::Latin,
+        Script::Greek,
+        Script::Cyrillic,
+        Script::Hebrew,
+        Script::Arabic,
+        Script::Devanagari,
+        Script::Thai,
+        Script::Hangul,
+        Script::Cjk,
+    ];
+
+    pub fn get_name(self) -> &'static str {
+        match self {
+            Script::Common => "common",
+            Script::Latin => "latin",
+            Script::Greek => "greek",
+            Script::Cyrillic => "cyrillic",
+            Script::Hebrew => "hebrew",
+            Script::Arabic => "arabic",
+            Script::Devanagari => "devanagari",
+            Script::Thai => "thai",
+            Script::Hangul => "hangul",
+            Script::Cjk => "cjk",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Script> {
+        Script::ALL
+            .iter()
+            .copied()
+            .find(|script| script.get_name() == name)
+    }
+
+    pub fn of(c: char) -> Script {
+        match c as u32 {
+            0x41..=0x5a | 0x61..=0x7a | 0xc0..=0x24f | 0x1e00..=0x1eff => match c {
+                '×' | '÷' => Script::Common,
+                _ => Script::Latin,
+            },
+            0x370..=0x3ff | 0x1f00..=0x1fff => Script::Greek,
+            0x400..=0x52f => Script::Cyrillic,
+            0x590..=0x5ff => Script::Hebrew,
+            0x600..=0x6ff | 0x750..=0x77f => Script::Arabic,
+            0x900..=0x97f => Script::Devanagari,
+            0xe00..=0xe7f => Script::Thai,
+            0x1100..=0x11ff | 0x3130..=0x318f | 0xac00..=0xd7af => Script::Hangul,
+            0x2e80..=0x2fff
+            | 0x3000..=0x30ff
+            | 0x3400..=0x4dbf
+            | 0x4e00..=0x9fff
+            | 0xf900..=0xfaff
+            | 0xff00..=0xffef => Script::Cjk,
+            _ => Script::Common,
+        }
+    }
+}
+
+// Splits the text where the script changes. Common characters stay in the run
+// they are in, or the one after them at the start, so a run is only Common if
+// the whole text is.
+pub fn split(text: &str) -> Vec<(Script, &str)> {
+    let mut runs: Vec<(Script, &str)> = Vec::new();
+    let mut start = 0;
+    let mut script = Script::Common;
+    for (idx, c) in text.char_indices() {
+        let next = Script::of(c);
+        if next == Script::Common || next == script {
+            continue;
+        }
+        if script == Script::Common {
+            script = next;
+        } else {
+            runs.push((script, &text[start..idx]));
+            start = idx;
+            script = next;
+        }
+    }
+    if start < text.len() {
+        runs.push((script, &text[start..]));
+    }
+    runs
+}
+
+// the font a run of the script is drawn with, given by "font.<script>" in the
+// strings, or "font.default" when the script has none of its own
+pub fn get_font_name(strings: &Strings, script: Script) -> Option<&str> {
+    strings
+        .find(&format!("font.{}", script.get_name()))
+        .or_else(|| strings.find("font.default"))
+}
@@ -0,0 +1,93 @@
+use std::collections::HashMap;
+
+use crate::parse::ParseError;
+
+// the English text the game ships with, which every other language falls
+// back to for anything it leaves out
+pub const DEFAULT_STRINGS: &str = include_str!("../../resources/lang/en.txt");
+
+// Text shown to the player looked up by id, so it can be given in other
+// languages. Read one per line as "<id> <text>", with \n for a new line.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Strings {
+    table: HashMap<String, String>,
+}
+
+impl Default for Strings {
+    fn default() -> Self {
+        Strings::parse(DEFAULT_STRINGS).unwrap_or_else(|_| Strings {
+            table: HashMap::new(),
+        })
+    }
+}
+
+impl Strings {
+    // Every error in the file is collected, like with the level
+    pub fn parse(contents: &str) -> Result<Strings, Vec<ParseError>> {
+        let mut table = HashMap::new();
+        let mut errors = Vec::new();
+        for (idx, line) in contents.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let id = line.split_whitespace().next().unwrap_or("");
+            let text = line[id.len()..].trim();
+            let message = if text.is_empty() {
+                format!("no text given for {}", id)
+            } else if table.contains_key(id) {
+                format!("{} is given twice", id)
+            } else {
+                table.insert(id.to_string(), text.replace("\\n", "\n"));
+                continue;
+            };
+            errors.push(ParseError {
+                line: idx + 1,
+                message,
+            });
+        }
+        if errors.is_empty() {
+            Ok(Strings { table })
+        } else {
+            Err(errors)
+        }
+    }
+
+    // takes whatever this is missing from the other language
+    pub fn with_fallback(mut self, fallback: &Strings) -> Strings {
+        for (id, text) in &fallback.table {
+            self.table.entry(id.clone()).or_insert_with(|| text.clone());
+        }
+        self
+    }
+
+    pub fn find(&self, id: &str) -> Option<&str> {
+        self.table.get(id).map(|text| text.as_str())
+    }
+
+    // an id that isn't given anywhere is shown as it is, so it gets noticed
+    pub fn get<'a>(&'a self, id: &'a str) -> &'a str {
+        self.find(id).unwrap_or(id)
+    }
+
+    // text from the level and the dialogue is either given as it is, or as
+    // "@<id>" to be looked up
+    pub fn resolve<'a>(&'a self, text: &'a str) -> &'a str {
+        match text.strip_prefix('@') {
+            Some(id) => self.get(id),
+            None => text,
+        }
+    }
+
+    // the ids the other language has that this one doesn't, sorted
+    pub fn get_missing<'a>(&self, other: &'a Strings) -> Vec<&'a str> {
+        let mut missing: Vec<&str> = other
+            .table
+            .keys()
+            .filter(|id| !self.table.contains_key(*id))
+            .map(|id| id.as_str())
+            .collect();
+        missing.sort_unstable();
+        missing
+    }
+}
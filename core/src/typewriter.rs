@@ -0,0 +1,121 @@
+// seconds per character at normal speed
+pub const TEXT_RATE: f32 = 0.3f32;
+// seconds per character while fast forwarding
+pub const TEXT_FAST_RATE: f32 = 0.1f32;
+// letting go sooner than this counts as a tap, which finishes the line
+pub const TAP_TIME: f32 = 0.2f32;
+// the typing sound plays at most this often however fast the text goes
+pub const TEXT_SFX_INTERVAL: f32 = 0.09f32;
+// a speed that shows every line in full right away
+pub const INSTANT: f32 = f32::INFINITY;
+
+// Types text out a character at a time. Holding the button down fast forwards
+// and tapping it shows the rest of the line right away.
+#[derive(Clone, Debug)]
+pub struct Typewriter {
+    text: String,
+    // still to be typed, reversed so the next one can be popped off
+    remaining: String,
+    // how many times faster than usual it types
+    speed: f32,
+    timer: f32,
+    // how long the button has been held, if it is
+    held_time: Option<f32>,
+    sfx_timer: f32,
+}
+
+impl Typewriter {
+    pub fn new(speed: f32) -> Self {
+        Self {
+            text: String::new(),
+            remaining: String::new(),
+            speed,
+            timer: 0f32,
+            held_time: None,
+            sfx_timer: 0f32,
+        }
+    }
+
+    pub fn start(&mut self, text: &str) {
+        if self.speed == INSTANT {
+            self.set_text(text);
+            return;
+        }
+        self.text.clear();
+        self.remaining = text.chars().rev().collect();
+        self.timer = self.get_rate();
+        self.sfx_timer = 0f32;
+    }
+
+    // shows all of the text without typing it out
+    pub fn set_text(&mut self, text: &str) {
+        self.text = text.to_string();
+        self.remaining.clear();
+    }
+
+    pub fn clear(&mut self) {
+        self.set_text("");
+    }
+
+    pub fn get_text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn is_typing(&self) -> bool {
+        !self.remaining.is_empty()
+    }
+
+    pub fn complete(&mut self) {
+        while let Some(c) = self.remaining.pop() {
+            self.text.push(c);
+        }
+    }
+
+    pub fn press(&mut self) {
+        self.held_time = Some(0f32);
+    }
+
+    pub fn release(&mut self) {
+        if let Some(held_time) = self.held_time.take() {
+            if held_time < TAP_TIME {
+                self.complete();
+            }
+        }
+    }
+
+    fn get_rate(&self) -> f32 {
+        match self.held_time {
+            Some(held_time) if held_time >= TAP_TIME => TEXT_FAST_RATE / self.speed,
+            _ => TEXT_RATE / self.speed,
+        }
+    }
+
+    // returns whether the typing sound should play
+    pub fn update(&mut self, dt: f32) -> bool {
+        if let Some(held_time) = &mut self.held_time {
+            *held_time += dt;
+        }
+        self.sfx_timer -= dt;
+        if self.remaining.is_empty() {
+            return false;
+        }
+        // fast forwarding starts with the next character
+        self.timer = self.timer.min(self.get_rate());
+        self.timer -= dt;
+        let mut typed = false;
+        while self.timer <= 0f32 {
+            match self.remaining.pop() {
+                Some(c) => self.text.push(c),
+                None => break,
+            }
+            self.timer += self.get_rate();
+            typed = true;
+        }
+        if typed && self.sfx_timer <= 0f32 {
+            self.sfx_timer = TEXT_SFX_INTERVAL;
+            true
+        } else {
+            false
+        }
+    }
+}
@@ -0,0 +1,1190 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::dialogue::{self, Choice, Dialogue, Line, Trigger};
+use crate::door::{Door, DOOR_HEIGHT, DOOR_WIDTH};
+use crate::flags::Flags;
+use crate::input::{Action, MouseButton};
+use crate::interactable::{Interactable, InteractableType};
+use crate::level::{AmbientLayout, Level};
+use crate::player::{Player, PLAYER_WIDTH};
+use crate::puzzle::{Puzzle, PuzzleID};
+use crate::save::Save;
+use crate::strings::Strings;
+use crate::typewriter::Typewriter;
+
+pub const SCREEN_WIDTH: f32 = 800f32;
+pub const SCREEN_HEIGHT: f32 = 600f32;
+pub const GROUND_HEIGHT: f32 = 50f32;
+pub const SHIP_DRAW_OFFSET: [f32; 2] = [100f32, 380f32];
+
+const DARKNESS_PAN_RATE: f32 = 40f32;
+const FLICKER_TIME: [f32; 6] = [1f32, 0.1f32, 0.85f32, 0.07f32, 0.12f32, 0.09f32];
+const FLICKER_STATE: [bool; 6] = [true, false, true, false, true, false];
+const IN_POD_TEXT_WAIT_TIME: f32 = 1f32;
+const GET_OUT_OF_POD_TIME: f32 = 3f32;
+const PLAYER_MOVEMENT_SPEED: f32 = 200f32;
+const DOOR_EXIT_ENTER_TIME: f32 = 1f32;
+const BAD_NEWS_FLICKER_RATE: f32 = 0.08f32;
+const BAD_NEWS_GROW_RATE: f32 = 100f32;
+const BAD_NEWS_NEW_ROOM_CHANGE: f32 = 150f32;
+const BAD_NEWS_DEATH_OFFSET: f32 = 2700f32;
+const SHIP_TRAVEL_TIME: f32 = 14f32;
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum State {
+    InPodInDarkness,
+    InPodWakeupText,
+    GetOutOfPod,
+    Investigate,
+    EnterDoor(Room),
+    ExitDoor,
+    InPuzzle(PuzzleID),
+    Ending,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub enum Room {
+    StasisPod,
+    LeftOfPod,
+    MainHallFrontOfPod,
+    WindowRightHall,
+    LeftHall,
+    FarRightHall,
+    Computer,
+    Final,
+}
+
+impl Room {
+    pub const ALL: [Room; 8] = [
+        Room::StasisPod,
+        Room::LeftOfPod,
+        Room::MainHallFrontOfPod,
+        Room::WindowRightHall,
+        Room::LeftHall,
+        Room::FarRightHall,
+        Room::Computer,
+        Room::Final,
+    ];
+
+    pub fn get_name(self) -> &'static str {
+        match self {
+            Room::StasisPod => "StasisPod",
+            Room::LeftOfPod => "LeftOfPod",
+            Room::MainHallFrontOfPod => "MainHallFrontOfPod",
+            Room::WindowRightHall => "WindowRightHall",
+            Room::LeftHall => "LeftHall",
+            Room::FarRightHall => "FarRightHall",
+            Room::Computer => "Computer",
+            Room::Final => "Final",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Room> {
+        Room::ALL
+            .iter()
+            .copied()
+            .find(|room| room.get_name() == name)
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub enum Difficulty {
+    Easy,
+    Normal,
+    Hard,
+}
+
+impl Difficulty {
+    pub fn get_name(self) -> &'static str {
+        match self {
+            Difficulty::Easy => "easy",
+            Difficulty::Normal => "normal",
+            Difficulty::Hard => "hard",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Difficulty> {
+        match name {
+            "easy" => Some(Difficulty::Easy),
+            "normal" => Some(Difficulty::Normal),
+            "hard" => Some(Difficulty::Hard),
+            _ => None,
+        }
+    }
+
+    // how fast the bad news spreads compared to normal
+    fn get_bad_news_scale(self) -> f32 {
+        match self {
+            Difficulty::Easy => 0.7f32,
+            Difficulty::Normal => 1f32,
+            Difficulty::Hard => 1.2f32,
+        }
+    }
+}
+
+// How a new game starts out
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct Options {
+    pub difficulty: Difficulty,
+    // scrambles the puzzles, which otherwise always start the same
+    pub seed: Option<u64>,
+    // starting in another room also skips the intro
+    pub start_room: Option<Room>,
+    pub skip_intro: bool,
+    // how many times faster than usual text is typed out
+    pub text_speed: f32,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            difficulty: Difficulty::Normal,
+            seed: None,
+            start_room: None,
+            skip_intro: false,
+            text_speed: 1f32,
+        }
+    }
+}
+
+enum WalkingState {
+    Standing,
+    Left,
+    Right,
+}
+
+// what is holding the text down, as only letting go of that lets it go
+#[derive(Copy, Clone, PartialEq, Debug)]
+enum TextPress {
+    Action(Action),
+    Mouse(MouseButton),
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub enum DoorIDs {
+    LeftOfPod,
+    LeftHall,
+}
+
+impl DoorIDs {
+    pub fn from_name(name: &str) -> Option<DoorIDs> {
+        match name {
+            "LeftOfPod" => Some(DoorIDs::LeftOfPod),
+            "LeftHall" => Some(DoorIDs::LeftHall),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub enum Sfx {
+    Text,
+    Door,
+    Error,
+    Success,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub enum Music {
+    Main,
+    Discovery,
+    BadNews,
+    Ending,
+}
+
+// Things that happened during a step that the presentation layer may want to
+// play or show, collected until drained with take_events
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Event {
+    PlaySfx(Sfx),
+    // made by something at this x position in the room
+    PlaySfxAt(Sfx, f32),
+    PlayMusic(Music),
+    StopMusic(Music),
+    // played once over the current track, which carries on after
+    PlayStinger(Music),
+    // a flag was set or a conversation was had, so the save has changed
+    Save,
+    // the escape is over, so the next run starts again from nothing
+    ClearSave,
+}
+
+pub struct World {
+    player: Player,
+    state: State,
+    typewriter: Typewriter,
+    darkness_yoffset: f32,
+    timer: f32,
+    draw_flicker_pod: bool,
+    index: usize,
+    room: Room,
+    walking_state: WalkingState,
+    interactables: Vec<Interactable>,
+    doors: Vec<Door>,
+    // (is_open, is_unlocked)
+    door_states: HashMap<DoorIDs, (bool, bool)>,
+    saw_earth: bool,
+    puzzle_states: HashMap<PuzzleID, bool>,
+    puzzle: Option<Puzzle>,
+    end_game: bool,
+    bad_news_state: usize,
+    bad_news_timer: f32,
+    bad_news_xoffset: f32,
+    bad_news_started: bool,
+    is_dead: bool,
+    events: Vec<Event>,
+    level: Level,
+    dialogue: Dialogue,
+    // the names of the conversations that have been started
+    played: HashSet<String>,
+    // (conversation, line) of what is being said
+    conversation: Option<(usize, usize)>,
+    dialogue_text: Typewriter,
+    text_press: Option<TextPress>,
+    // the choice picked out of those of the current line
+    choice: usize,
+    flags: Flags,
+    // the index of what was talked about, which is used once the
+    // conversation is over
+    after_conversation: Option<usize>,
+    strings: Strings,
+    options: Options,
+}
+
+impl World {
+    pub fn new() -> Self {
+        Self::with_level(Level::default())
+    }
+
+    pub fn with_level(level: Level) -> Self {
+        Self::with_options(level, Options::default())
+    }
+
+    pub fn with_options(level: Level, options: Options) -> Self {
+        let mut world = Self {
+            player: Player::new(),
+            state: State::InPodInDarkness,
+            typewriter: Typewriter::new(options.text_speed),
+            darkness_yoffset: 0f32,
+            timer: FLICKER_TIME[0],
+            draw_flicker_pod: false,
+            index: 0usize,
+            room: Room::StasisPod,
+            walking_state: WalkingState::Standing,
+            interactables: Vec::new(),
+            doors: Vec::new(),
+            door_states: HashMap::new(),
+            saw_earth: false,
+            puzzle_states: HashMap::new(),
+            puzzle: None,
+            end_game: false,
+            bad_news_state: 0,
+            bad_news_timer: 0f32,
+            bad_news_xoffset: 0f32,
+            bad_news_started: false,
+            is_dead: false,
+            events: Vec::new(),
+            level,
+            dialogue: Dialogue::default(),
+            played: HashSet::new(),
+            conversation: None,
+            dialogue_text: Typewriter::new(options.text_speed),
+            text_press: None,
+            choice: 0,
+            flags: Flags::new(),
+            after_conversation: None,
+            strings: Strings::default(),
+            options,
+        };
+        if options.skip_intro || options.start_room.is_some() {
+            world.skip_intro(options.start_room.unwrap_or(Room::StasisPod));
+        }
+        world
+    }
+
+    // the player as they are right after getting out of the pod
+    fn skip_intro(&mut self, room: Room) {
+        self.player.x = 500f32;
+        self.player.y = 430f32;
+        self.player.rot = 0f32;
+        self.room = room;
+        self.state = State::Investigate;
+        self.events.push(Event::PlayMusic(Music::Main));
+        self.init_room();
+    }
+
+    pub fn get_options(&self) -> Options {
+        self.options
+    }
+
+    // Swaps in a changed level and lays out the current room again, keeping
+    // everything that happened so far
+    pub fn set_level(&mut self, level: Level) {
+        self.level = level;
+        match self.state {
+            State::InPodInDarkness | State::InPodWakeupText | State::GetOutOfPod => (),
+            _ => self.init_room(),
+        }
+    }
+
+    // a conversation that is going on is stopped as its lines may have changed
+    pub fn set_dialogue(&mut self, dialogue: Dialogue) {
+        self.dialogue = dialogue;
+        self.conversation = None;
+        self.after_conversation = None;
+    }
+
+    // the text of the room is shown again in the new language, a line that is
+    // being said stays as it was
+    pub fn set_strings(&mut self, strings: Strings) {
+        self.strings = strings;
+        match self.state {
+            State::InPodInDarkness | State::InPodWakeupText | State::GetOutOfPod => (),
+            _ => {
+                let layout = self.level.get_room(self.room);
+                if let Some(text) = layout.and_then(|layout| layout.text.as_ref()) {
+                    self.typewriter.set_text(self.strings.resolve(text));
+                }
+            }
+        }
+    }
+
+    pub fn get_strings(&self) -> &Strings {
+        &self.strings
+    }
+
+    pub fn get_flags(&self) -> &Flags {
+        &self.flags
+    }
+
+    pub fn get_save(&self) -> Save {
+        Save {
+            flags: self.flags.clone(),
+            played: self.played.iter().cloned().collect(),
+        }
+    }
+
+    // for picking up with what was kept from the runs before
+    pub fn load_save(&mut self, save: &Save) {
+        self.flags = save.flags.clone();
+        self.played = save.played.iter().cloned().collect();
+    }
+
+    pub fn get_player(&self) -> &Player {
+        &self.player
+    }
+
+    pub fn get_state(&self) -> State {
+        self.state
+    }
+
+    pub fn get_room(&self) -> Room {
+        self.room
+    }
+
+    pub fn get_text(&self) -> &str {
+        self.typewriter.get_text()
+    }
+
+    pub fn is_talking(&self) -> bool {
+        self.conversation.is_some() && self.state == State::Investigate
+    }
+
+    // (speaker, what has been typed of their line so far)
+    pub fn get_dialogue(&self) -> Option<(&str, &str)> {
+        if !self.is_talking() {
+            return None;
+        }
+        let line = self.get_line()?;
+        let speaker = self
+            .strings
+            .find(&format!("speaker.{}", line.speaker))
+            .unwrap_or(&line.speaker);
+        Some((speaker, self.dialogue_text.get_text()))
+    }
+
+    // (choices, the one picked out) once a line that has them is typed out
+    pub fn get_choices(&self) -> Option<(&[Choice], usize)> {
+        if !self.is_talking() || self.dialogue_text.is_typing() {
+            return None;
+        }
+        let line = self.get_line()?;
+        if line.choices.is_empty() {
+            None
+        } else {
+            Some((&line.choices, self.choice))
+        }
+    }
+
+    // the image of whoever is talking
+    pub fn get_portrait(&self) -> Option<&str> {
+        if !self.is_talking() {
+            return None;
+        }
+        self.dialogue.get_portrait(&self.get_line()?.speaker)
+    }
+
+    pub fn get_darkness_yoffset(&self) -> f32 {
+        self.darkness_yoffset
+    }
+
+    pub fn get_draw_flicker_pod(&self) -> bool {
+        self.draw_flicker_pod
+    }
+
+    pub fn get_doors(&self) -> &[Door] {
+        &self.doors
+    }
+
+    pub fn get_interactables(&self) -> &[Interactable] {
+        &self.interactables
+    }
+
+    pub fn get_door_state(&self, id: DoorIDs) -> Option<(bool, bool)> {
+        self.door_states.get(&id).copied()
+    }
+
+    pub fn is_puzzle_solved(&self, id: PuzzleID) -> bool {
+        self.puzzle_states.get(&id).copied().unwrap_or(false)
+    }
+
+    pub fn get_puzzle(&self) -> Option<&Puzzle> {
+        self.puzzle.as_ref()
+    }
+
+    pub fn is_end_game(&self) -> bool {
+        self.end_game
+    }
+
+    pub fn is_dead(&self) -> bool {
+        self.is_dead
+    }
+
+    // the sounds that should be looping right now, nothing once out in space
+    pub fn get_ambient(&self) -> &[AmbientLayout] {
+        let layout = match self.level.get_room(self.room) {
+            Some(layout) if self.state != State::Ending => layout,
+            _ => return &[],
+        };
+        if self.end_game && !layout.end_game_ambient.is_empty() {
+            &layout.end_game_ambient
+        } else {
+            &layout.ambient
+        }
+    }
+
+    pub fn get_bad_news_state(&self) -> usize {
+        self.bad_news_state
+    }
+
+    pub fn get_bad_news_xoffset(&self) -> f32 {
+        self.bad_news_xoffset
+    }
+
+    // 0 when the escape ship leaves and 1 once it reaches its destination
+    pub fn get_ending_progress(&self) -> f32 {
+        if self.state == State::Ending {
+            self.timer / SHIP_TRAVEL_TIME
+        } else {
+            0f32
+        }
+    }
+
+    pub fn take_events(&mut self) -> Vec<Event> {
+        std::mem::take(&mut self.events)
+    }
+
+    pub fn is_interactable_in_range(&self, interactable: &Interactable) -> bool {
+        let (x, y) = self.player.get_interaction_point();
+        interactable.is_within_range(x, y)
+    }
+
+    pub fn is_door_in_range(&self, door: &Door) -> bool {
+        let (x, y) = self.player.get_interaction_point();
+        door.is_within_range(x, y)
+    }
+
+    // (left, right) exits that are currently available from this room
+    pub fn get_room_exits(&self) -> (bool, bool) {
+        (
+            self.get_exit(false).is_some(),
+            self.get_exit(true).is_some(),
+        )
+    }
+
+    pub fn reset_puzzle(&mut self) {
+        if let Some(puzzle) = &mut self.puzzle {
+            puzzle.reset();
+        }
+    }
+
+    pub fn skip_puzzle(&mut self) {
+        if let Some(puzzle) = &mut self.puzzle {
+            puzzle.skip();
+        }
+    }
+
+    fn init_room(&mut self) {
+        self.doors.clear();
+        self.interactables.clear();
+        let layout = match self.level.get_room(self.room) {
+            Some(layout) => layout,
+            None => return,
+        };
+        if let Some(text) = &layout.text {
+            self.typewriter.set_text(self.strings.resolve(text));
+        }
+        self.darkness_yoffset = layout.darkness_yoffset;
+
+        for (idx, door_layout) in layout.doors.iter().enumerate() {
+            let mut door = Door::new(
+                door_layout.is_open,
+                door_layout.x,
+                SCREEN_HEIGHT - DOOR_HEIGHT - GROUND_HEIGHT,
+                idx,
+            );
+            if let Some(id) = door_layout.id {
+                if let Some((is_open, _)) = self.door_states.get(&id) {
+                    door.set_open(*is_open);
+                }
+            }
+            self.doors.push(door);
+        }
+        for interactable_layout in &layout.interactables {
+            let mut interactable = Interactable::new(
+                interactable_layout.itype,
+                interactable_layout.x,
+                interactable_layout.y,
+            );
+            if let Some(radius) = interactable_layout.radius {
+                interactable.set_radius(radius);
+            }
+            if let Some(id) = interactable_layout.door_id {
+                if let Some((_, is_unlocked)) = self.door_states.get(&id) {
+                    interactable.set_unlocked(*is_unlocked);
+                }
+            }
+            if let InteractableType::Puzzle(id, _) = interactable_layout.itype {
+                if let Some(true) = self.puzzle_states.get(&id) {
+                    interactable.set_puzzle_cleared(true);
+                }
+            }
+            self.interactables.push(interactable);
+        }
+
+        if self.room == Room::WindowRightHall && !self.saw_earth {
+            self.saw_earth = true;
+            self.events.push(Event::PlayStinger(Music::Discovery));
+        }
+        self.start_conversation(Trigger::Enter(self.room));
+    }
+
+    // the room walking off that side leads to, if it is open
+    fn get_exit(&self, right: bool) -> Option<Room> {
+        let layout = self.level.get_room(self.room)?;
+        let exit = if right {
+            layout.right.as_ref()
+        } else {
+            layout.left.as_ref()
+        }?;
+        if exit.escape && !self.end_game {
+            None
+        } else {
+            Some(exit.to)
+        }
+    }
+
+    // the bad news stays where it is on the ship, so it moves on the screen by
+    // how far apart the rooms are
+    fn move_bad_news(&mut self, to: Room) {
+        if !self.end_game {
+            return;
+        }
+        let position = |room| {
+            self.level
+                .get_room(room)
+                .map_or(0f32, |layout| layout.position)
+        };
+        self.bad_news_xoffset += (position(self.room) - position(to)) * BAD_NEWS_NEW_ROOM_CHANGE;
+    }
+
+    fn check_exit_left(&mut self) {
+        if let Some(room) = self.get_exit(false) {
+            self.move_bad_news(room);
+            self.room = room;
+            self.player.x = SCREEN_WIDTH - 70f32 - PLAYER_WIDTH;
+            self.init_room();
+        }
+    }
+
+    fn check_exit_right(&mut self) {
+        if let Some(room) = self.get_exit(true) {
+            self.move_bad_news(room);
+            self.room = room;
+            self.player.x = 70f32;
+            self.init_room();
+        }
+    }
+
+    fn check_exit_door(&mut self, door_idx: usize) {
+        let to = match self
+            .level
+            .get_room(self.room)
+            .and_then(|layout| layout.doors.get(door_idx))
+        {
+            Some(door) if door_idx < self.doors.len() => door.to,
+            _ => return,
+        };
+        self.state = State::EnterDoor(to);
+        self.timer = DOOR_EXIT_ENTER_TIME;
+        self.player.x = self.doors[door_idx].get_x() + (DOOR_WIDTH - PLAYER_WIDTH) / 2f32;
+        self.player.set_walking(true);
+        self.move_bad_news(to);
+    }
+
+    fn use_interactable(&mut self, itype: InteractableType) {
+        match itype {
+            InteractableType::Door(idx) => self.toggle_door(idx),
+            InteractableType::LockedDoor(idx, unlocked) => {
+                if unlocked {
+                    self.toggle_door(idx);
+                } else {
+                    self.events.push(Event::PlaySfx(Sfx::Error));
+                }
+            }
+            InteractableType::Puzzle(id, cleared) => {
+                let layout = match self.level.get_puzzle(id) {
+                    Some(layout) if !cleared => layout,
+                    _ => return,
+                };
+                self.puzzle = Some(match self.options.seed {
+                    // each puzzle gets its own scramble from the seed
+                    Some(seed) => Puzzle::with_seed(id, layout, seed ^ id as u64),
+                    None => Puzzle::new(id, layout),
+                });
+                self.state = State::InPuzzle(id);
+            }
+            InteractableType::Ship => {
+                self.state = State::Ending;
+                self.events.push(Event::StopMusic(Music::BadNews));
+                self.events.push(Event::PlayMusic(Music::Ending));
+                self.events.push(Event::ClearSave);
+                self.timer = 0f32;
+            }
+            InteractableType::Examine => (),
+        }
+    }
+
+    // the door is heard from where it is, which may be away from its switch,
+    // and a door with an id is remembered as open or closed
+    fn toggle_door(&mut self, idx: usize) {
+        let is_open = match self.doors.get_mut(idx) {
+            Some(door) => {
+                self.events
+                    .push(Event::PlaySfxAt(Sfx::Door, door.get_center_x()));
+                door.toggle_open()
+            }
+            None => {
+                self.events.push(Event::PlaySfx(Sfx::Door));
+                return;
+            }
+        };
+        let door_id = match self
+            .level
+            .get_room(self.room)
+            .and_then(|layout| layout.doors.get(idx))
+            .and_then(|door| door.id)
+        {
+            Some(door_id) => door_id,
+            None => return,
+        };
+        if let Some(state) = self.door_states.get_mut(&door_id) {
+            state.0 = is_open;
+        } else {
+            self.door_states.insert(door_id, (is_open, true));
+        }
+    }
+
+    fn handle_solved_puzzle(&mut self, id: PuzzleID) {
+        self.puzzle_states.insert(id, true);
+        self.puzzle = None;
+        for interactable in &mut self.interactables {
+            if interactable.get_type() == InteractableType::Puzzle(id, false) {
+                interactable.set_puzzle_cleared(true);
+            }
+        }
+        match id {
+            PuzzleID::FarRightHall => {
+                self.door_states.insert(DoorIDs::LeftHall, (false, true));
+                self.door_states.insert(DoorIDs::LeftOfPod, (false, false));
+            }
+            PuzzleID::Computer => {
+                self.end_game = true;
+            }
+        }
+        self.events.push(Event::PlaySfx(Sfx::Success));
+        self.start_conversation(Trigger::Solve(id));
+    }
+
+    fn find_interactable(&self, x: Option<f32>, y: Option<f32>) -> Option<usize> {
+        for (idx, interactable) in self.interactables.iter().enumerate() {
+            if !self.is_interactable_in_range(interactable) {
+                continue;
+            }
+            if let (Some(x), Some(y)) = (x, y) {
+                if !interactable.is_within_range(x, y) {
+                    continue;
+                }
+            }
+            return Some(idx);
+        }
+        None
+    }
+
+    // the first time something is used there may be something to say about it
+    fn interact(&mut self, idx: usize) {
+        let name = self
+            .level
+            .get_room(self.room)
+            .and_then(|layout| layout.interactables.get(idx))
+            .and_then(|layout| layout.name.clone());
+        // what is said about an examine spot by name comes first
+        let examined = match name {
+            Some(name) => self.start_conversation(Trigger::Examine(name)),
+            None => false,
+        };
+        if examined || self.start_conversation(Trigger::Interact(self.room, idx)) {
+            self.after_conversation = Some(idx);
+        } else {
+            self.use_interactable_at(idx);
+        }
+    }
+
+    // the flag the level asks for is checked after anything said about it, as
+    // that may have set it
+    fn use_interactable_at(&mut self, idx: usize) {
+        let itype = match self.interactables.get(idx) {
+            Some(interactable) => interactable.get_type(),
+            None => return,
+        };
+        let condition = self
+            .level
+            .get_room(self.room)
+            .and_then(|layout| layout.interactables.get(idx))
+            .and_then(|layout| layout.condition.as_ref());
+        let allowed = match condition {
+            Some((flag, is_set)) => self.flags.is_set(flag) == *is_set,
+            None => true,
+        };
+        if allowed {
+            self.use_interactable(itype);
+        } else {
+            self.events.push(Event::PlaySfx(Sfx::Error));
+        }
+    }
+
+    fn get_line(&self) -> Option<&Line> {
+        let (conversation, line) = self.conversation?;
+        self.dialogue
+            .get_conversation(conversation)?
+            .lines
+            .get(line)
+    }
+
+    // returns false if there is nothing left to say for the trigger
+    fn start_conversation(&mut self, trigger: Trigger) -> bool {
+        let idx = match self.dialogue.find(&trigger, &self.played, &self.flags) {
+            Some(idx) => idx,
+            None => return false,
+        };
+        if let Some(conversation) = self.dialogue.get_conversation(idx) {
+            self.played.insert(conversation.name.clone());
+            for flag in &conversation.sets {
+                self.flags.set(flag);
+            }
+            self.events.push(Event::Save);
+        }
+        self.conversation = Some((idx, 0));
+        self.walking_state = WalkingState::Standing;
+        self.start_line();
+        true
+    }
+
+    fn start_line(&mut self) {
+        self.choice = 0;
+        match self.get_line().map(|line| line.text.clone()) {
+            Some(text) => self.dialogue_text.start(self.strings.resolve(&text)),
+            None => {
+                self.conversation = None;
+                if let Some(idx) = self.after_conversation.take() {
+                    self.use_interactable_at(idx);
+                }
+            }
+        }
+    }
+
+    // the first press shows the whole line and the next goes on to the next one
+    fn advance_dialogue(&mut self, press: TextPress) {
+        if self.dialogue_text.is_typing() {
+            self.dialogue_text.press();
+            self.text_press = Some(press);
+        } else if self.get_choices().is_some() {
+            self.choose(self.choice);
+        } else {
+            self.next_line();
+        }
+    }
+
+    fn next_line(&mut self) {
+        if let Some((conversation, line)) = self.conversation {
+            self.conversation = Some((conversation, line + 1));
+            self.start_line();
+        }
+    }
+
+    // sets the flag of the choice, and moves on to what is said about it if
+    // there is anything
+    fn choose(&mut self, idx: usize) {
+        let flag = match self.get_choices() {
+            Some((choices, _)) if idx < choices.len() => choices[idx].flag.clone(),
+            _ => return,
+        };
+        self.flags.set(&flag);
+        self.events.push(Event::Save);
+        if !self.start_conversation(Trigger::Flag(flag)) {
+            self.next_line();
+        }
+    }
+
+    fn move_choice(&mut self, down: bool) {
+        if let Some((choices, choice)) = self.get_choices() {
+            let count = choices.len();
+            self.choice = if down {
+                (choice + 1) % count
+            } else {
+                (choice + count - 1) % count
+            };
+        }
+    }
+
+    fn find_open_door(&self, x: Option<f32>, y: Option<f32>) -> Option<usize> {
+        for door in &self.doors {
+            if !door.get_open() || !self.is_door_in_range(door) {
+                continue;
+            }
+            if let (Some(x), Some(y)) = (x, y) {
+                if !door.is_within_range(x, y) {
+                    continue;
+                }
+            }
+            return Some(door.get_id());
+        }
+        None
+    }
+
+    fn release_text(&mut self, press: TextPress) {
+        if self.text_press == Some(press) {
+            self.text_press = None;
+            self.typewriter.release();
+            self.dialogue_text.release();
+        }
+    }
+
+    // once the text is done a press skips the wait after it, and the next one
+    // gets the player out of the pod
+    fn press_text(&mut self, press: TextPress) {
+        if self.typewriter.is_typing() {
+            self.typewriter.press();
+            self.text_press = Some(press);
+        } else if self.timer <= 0f32 {
+            self.state = State::GetOutOfPod;
+            self.timer = GET_OUT_OF_POD_TIME;
+            // the text stays up while the mouse is used
+            if let TextPress::Action(_) = press {
+                self.typewriter.clear();
+            }
+        } else {
+            self.timer = 0f32;
+        }
+    }
+
+    pub fn update(&mut self, dt: f32) {
+        self.player.begin_step();
+        if self.is_dead {
+            self.bad_news_timer += dt;
+            if self.bad_news_timer > BAD_NEWS_FLICKER_RATE {
+                self.bad_news_timer -= BAD_NEWS_FLICKER_RATE;
+                self.bad_news_state = (self.bad_news_state + 1) % 3;
+            }
+            return;
+        }
+        // everything waits while someone is talking
+        if self.is_talking() {
+            if self.dialogue_text.update(dt) {
+                self.events.push(Event::PlaySfx(Sfx::Text));
+            }
+            return;
+        }
+        match self.state {
+            State::InPodInDarkness => {
+                self.player.x = 520f32;
+                self.player.y = 350f32;
+                self.player.rot = 0.78f32;
+                self.timer -= dt;
+                if self.timer <= 0f32 {
+                    self.draw_flicker_pod = FLICKER_STATE[self.index];
+                    self.index = (self.index + 1) % 6;
+                    self.timer = FLICKER_TIME[self.index];
+                }
+                if self.darkness_yoffset > -300f32 {
+                    self.darkness_yoffset -= dt * DARKNESS_PAN_RATE;
+                } else {
+                    self.state = State::InPodWakeupText;
+                    self.timer = IN_POD_TEXT_WAIT_TIME;
+                    self.typewriter.start(self.strings.get("game.wake_up"));
+                }
+            }
+            State::InPodWakeupText => {
+                if self.typewriter.is_typing() {
+                    if self.typewriter.update(dt) {
+                        self.events.push(Event::PlaySfx(Sfx::Text));
+                    }
+                } else {
+                    self.timer -= dt;
+                    if self.timer <= 0f32 {
+                        self.timer = 0f32;
+                    }
+                }
+            }
+            State::GetOutOfPod => {
+                self.timer -= dt;
+                if self.timer > 0f32 {
+                    let lerp = self.timer / GET_OUT_OF_POD_TIME;
+                    self.player.x = (520f32 * lerp) + (500f32 * (1f32 - lerp));
+                    self.player.y = (350f32 * lerp) + (430f32 * (1f32 - lerp));
+                    self.player.rot = (0.78f32 * lerp) + (0f32 * (1f32 - lerp));
+                } else if self.timer <= 0f32 {
+                    self.state = State::Investigate;
+                    self.events.push(Event::PlayMusic(Music::Main));
+                    self.init_room();
+                }
+            }
+            State::Investigate => match self.walking_state {
+                WalkingState::Standing => {
+                    self.player.set_walking(false);
+                }
+                WalkingState::Left => {
+                    self.player.x -= dt * PLAYER_MOVEMENT_SPEED;
+                    if self.player.x <= 0f32 {
+                        self.player.x = 0f32;
+                        self.walking_state = WalkingState::Standing;
+                        self.player.set_walking(false);
+                        self.check_exit_left();
+                    } else {
+                        self.player.set_walking(true);
+                        self.player.set_xflip(true);
+                    }
+                }
+                WalkingState::Right => {
+                    self.player.x += dt * PLAYER_MOVEMENT_SPEED;
+                    if self.player.x + PLAYER_WIDTH >= SCREEN_WIDTH {
+                        self.player.x = SCREEN_WIDTH - PLAYER_WIDTH;
+                        self.walking_state = WalkingState::Standing;
+                        self.player.set_walking(false);
+                        self.check_exit_right();
+                    } else {
+                        self.player.set_walking(true);
+                        self.player.set_xflip(false);
+                    }
+                }
+            },
+            State::EnterDoor(room) => {
+                self.timer -= dt;
+                if self.timer <= 0f32 {
+                    let from = self.room;
+                    self.room = room;
+                    self.state = State::ExitDoor;
+                    self.timer = DOOR_EXIT_ENTER_TIME;
+                    self.init_room();
+                    // out through the door back, or the first one
+                    let door = self
+                        .level
+                        .get_room(room)
+                        .and_then(|layout| layout.doors.iter().position(|door| door.to == from))
+                        .and_then(|idx| self.doors.get(idx))
+                        .or_else(|| self.doors.first());
+                    if let Some(door) = door {
+                        self.player.x = door.get_x() + (DOOR_WIDTH - PLAYER_WIDTH) / 2f32;
+                    }
+                    self.player.opacity = 0f32;
+                } else {
+                    self.player.opacity = self.timer / DOOR_EXIT_ENTER_TIME;
+                }
+            }
+            State::ExitDoor => {
+                self.timer -= dt;
+                if self.timer <= 0f32 {
+                    self.state = State::Investigate;
+                    self.player.set_walking(false);
+                    self.player.opacity = 1f32;
+                } else {
+                    self.player.opacity = 1f32 - self.timer / DOOR_EXIT_ENTER_TIME;
+                }
+            }
+            State::InPuzzle(id) => {
+                if let Some(puzzle) = &self.puzzle {
+                    if puzzle.is_solved() {
+                        self.handle_solved_puzzle(id);
+                    } else if puzzle.is_abort() {
+                        self.puzzle = None;
+                        self.state = State::Investigate;
+                    }
+                } else {
+                    self.state = State::Investigate;
+                }
+            }
+            State::Ending => {
+                self.timer += dt;
+                if self.timer >= SHIP_TRAVEL_TIME {
+                    self.timer = SHIP_TRAVEL_TIME;
+                }
+                return;
+            }
+        }
+        self.player.update(dt);
+        if self.end_game {
+            if !self.bad_news_started {
+                self.bad_news_started = true;
+                self.events.push(Event::StopMusic(Music::Main));
+                self.events.push(Event::PlayMusic(Music::BadNews));
+            }
+            self.bad_news_xoffset +=
+                dt * BAD_NEWS_GROW_RATE * self.options.difficulty.get_bad_news_scale();
+            if self.bad_news_xoffset >= BAD_NEWS_DEATH_OFFSET {
+                self.is_dead = true;
+            }
+            self.bad_news_timer += dt;
+            if self.bad_news_timer > BAD_NEWS_FLICKER_RATE {
+                self.bad_news_timer -= BAD_NEWS_FLICKER_RATE;
+                self.bad_news_state = (self.bad_news_state + 1) % 3;
+            }
+        }
+    }
+
+    pub fn mouse_down(&mut self, button: MouseButton, x: f32, y: f32) {
+        if self.is_talking() {
+            if self.get_choices().is_some() {
+                let clicked = (0..dialogue::MAX_CHOICES).find(|idx| {
+                    let rect = dialogue::get_choice_rect(*idx);
+                    x >= rect[0] && x <= rect[0] + rect[2] && y >= rect[1] && y <= rect[1] + rect[3]
+                });
+                if let Some(idx) = clicked {
+                    self.choose(idx);
+                }
+            } else {
+                self.advance_dialogue(TextPress::Mouse(button));
+            }
+            return;
+        }
+        match self.state {
+            State::InPodInDarkness => (),
+            State::InPodWakeupText => self.press_text(TextPress::Mouse(button)),
+            State::GetOutOfPod => (),
+            State::Investigate => {
+                if button == MouseButton::Left {
+                    if let Some(idx) = self.find_interactable(Some(x), Some(y)) {
+                        self.interact(idx);
+                    } else if self.player.x > x {
+                        self.walking_state = WalkingState::Left;
+                    } else if self.player.x + PLAYER_WIDTH < x {
+                        self.walking_state = WalkingState::Right;
+                    }
+                } else if button == MouseButton::Right {
+                    if let Some(idx) = self.find_open_door(Some(x), Some(y)) {
+                        self.check_exit_door(idx);
+                    }
+                }
+            }
+            State::EnterDoor(_) | State::ExitDoor => (),
+            State::InPuzzle(_) => {
+                if let Some(puzzle) = &mut self.puzzle {
+                    if button == MouseButton::Left {
+                        puzzle.handle_click(x, y);
+                    }
+                }
+            }
+            State::Ending => (),
+        }
+    }
+
+    pub fn mouse_up(&mut self, button: MouseButton, _x: f32, _y: f32) {
+        self.release_text(TextPress::Mouse(button));
+        if let State::Investigate = self.state {
+            if button == MouseButton::Left {
+                self.walking_state = WalkingState::Standing;
+            }
+        }
+    }
+
+    pub fn action_down(&mut self, action: Action) {
+        if self.is_talking() {
+            match action {
+                Action::Interact => self.advance_dialogue(TextPress::Action(action)),
+                Action::Up => self.move_choice(false),
+                Action::Down => self.move_choice(true),
+                _ => (),
+            }
+            return;
+        }
+        match self.state {
+            State::InPodInDarkness => (),
+            State::InPodWakeupText => self.press_text(TextPress::Action(action)),
+            State::GetOutOfPod => (),
+            State::Investigate => match action {
+                Action::Left => {
+                    if self.player.x > 0f32 {
+                        self.walking_state = WalkingState::Left;
+                    }
+                }
+                Action::Right => {
+                    if self.player.x + PLAYER_WIDTH < SCREEN_WIDTH {
+                        self.walking_state = WalkingState::Right;
+                    }
+                }
+                Action::Interact => {
+                    if let Some(idx) = self.find_interactable(None, None) {
+                        self.interact(idx);
+                    }
+                }
+                Action::Up | Action::EnterDoor => {
+                    if let Some(idx) = self.find_open_door(None, None) {
+                        self.check_exit_door(idx);
+                    }
+                }
+                Action::Down | Action::Back => (),
+            },
+            State::EnterDoor(_) | State::ExitDoor => (),
+            State::InPuzzle(_) => {
+                if let Some(puzzle) = &mut self.puzzle {
+                    puzzle.handle_action(action);
+                }
+            }
+            State::Ending => (),
+        }
+    }
+
+    pub fn action_up(&mut self, action: Action) {
+        self.release_text(TextPress::Action(action));
+        if let State::Investigate = self.state {
+            if action == Action::Left || action == Action::Right {
+                self.walking_state = WalkingState::Standing;
+            }
+        }
+    }
+}
+
+impl Default for World {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,145 @@
+#![allow(dead_code)]
+
+use ld47_core::input::{Action, MouseButton};
+use ld47_core::world::{Event, World};
+
+pub const DT: f32 = 1f32 / 60f32;
+
+// Drives a World one fixed step at a time the same way the game does, keeping
+// every event it emitted so tests can check what would have been played
+pub struct Harness {
+    pub world: World,
+    pub events: Vec<Event>,
+    pub steps: usize,
+}
+
+impl Harness {
+    pub fn new() -> Self {
+        Self::with_world(World::new())
+    }
+
+    pub fn with_world(world: World) -> Self {
+        Self {
+            world,
+            events: Vec::new(),
+            steps: 0,
+        }
+    }
+
+    pub fn step(&mut self) {
+        self.world.update(DT);
+        self.events.extend(self.world.take_events());
+        self.steps += 1;
+    }
+
+    pub fn run(&mut self, seconds: f32) {
+        for _ in 0..(seconds / DT) as usize {
+            self.step();
+        }
+    }
+
+    fn assert_done<F: Fn(&World) -> bool>(&self, seconds: f32, what: &str, done: F) {
+        assert!(
+            done(&self.world),
+            "Timed out after {} seconds waiting for {} (state {:?}, room {:?})",
+            seconds,
+            what,
+            self.world.get_state(),
+            self.world.get_room()
+        );
+    }
+
+    pub fn run_until<F: Fn(&World) -> bool>(&mut self, seconds: f32, what: &str, done: F) {
+        for _ in 0..(seconds / DT) as usize {
+            if done(&self.world) {
+                return;
+            }
+            self.step();
+        }
+        self.assert_done(seconds, what, done);
+    }
+
+    pub fn tap(&mut self, action: Action) {
+        self.world.action_down(action);
+        self.step();
+        self.world.action_up(action);
+        self.step();
+    }
+
+    // reads through whatever is being said, like a player tapping along
+    pub fn talk_through(&mut self) {
+        for _ in 0..100 {
+            if !self.world.is_talking() {
+                return;
+            }
+            self.tap(Action::Interact);
+        }
+        panic!("A conversation never ended");
+    }
+
+    pub fn press(&mut self, action: Action) {
+        self.tap(action);
+        self.talk_through();
+    }
+
+    pub fn click(&mut self, x: f32, y: f32) {
+        self.world.mouse_down(MouseButton::Left, x, y);
+        self.step();
+        self.world.mouse_up(MouseButton::Left, x, y);
+        self.step();
+        self.talk_through();
+    }
+
+    // anything said on the way is read through before carrying on
+    pub fn hold_until<F: Fn(&World) -> bool>(
+        &mut self,
+        action: Action,
+        seconds: f32,
+        what: &str,
+        done: F,
+    ) {
+        self.world.action_down(action);
+        for _ in 0..(seconds / DT) as usize {
+            if done(&self.world) {
+                break;
+            }
+            if self.world.is_talking() {
+                self.world.action_up(action);
+                self.talk_through();
+                self.world.action_down(action);
+            }
+            self.step();
+        }
+        self.assert_done(seconds, what, done);
+        self.world.action_up(action);
+        self.step();
+    }
+
+    pub fn walk_to_interactable(&mut self, idx: usize) {
+        let target = self.world.get_interactables()[idx].get_x();
+        let action = if self.world.get_player().x + 32f32 > target {
+            Action::Left
+        } else {
+            Action::Right
+        };
+        self.hold_until(action, 5f32, "the player to reach an interactable", |w| {
+            w.is_interactable_in_range(&w.get_interactables()[idx])
+        });
+    }
+
+    pub fn walk_to_door(&mut self, idx: usize) {
+        let target = self.world.get_doors()[idx].get_center_x();
+        let action = if self.world.get_player().x + 32f32 > target {
+            Action::Left
+        } else {
+            Action::Right
+        };
+        self.hold_until(action, 5f32, "the player to reach a door", |w| {
+            w.is_door_in_range(&w.get_doors()[idx])
+        });
+    }
+
+    pub fn saw_event(&self, event: Event) -> bool {
+        self.events.contains(&event)
+    }
+}
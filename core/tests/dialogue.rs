@@ -0,0 +1,352 @@
+mod common;
+
+use std::collections::HashSet;
+
+use common::Harness;
+use ld47_core::dialogue::{self, Dialogue, Trigger, DEFAULT_DIALOGUE};
+use ld47_core::flags::Flags;
+use ld47_core::input::{Action, MouseButton};
+use ld47_core::interactable::InteractableType;
+use ld47_core::level::Level;
+use ld47_core::puzzle::PuzzleID;
+use ld47_core::save::Save;
+use ld47_core::world::{Event, Options, Room, State, World};
+
+const DIALOGUE: &str = "\
+speaker You
+speaker Robot portrait_robot
+
+conversation hello
+on enter WindowRightHall
+line Robot Hello.
+line You   Hi there.
+
+conversation panel
+on interact FarRightHall 0
+line You A panel.
+
+";
+
+const CHOICES: &str = "\
+speaker You
+speaker Robot
+
+conversation window
+on enter WindowRightHall
+line Robot Pick one.
+choice red Red.
+choice blue Blue.
+choice green Green.
+line Robot Green it is.
+
+conversation red
+on flag red
+line Robot Red it is.
+
+conversation far_right_hall_red
+on enter FarRightHall
+if red
+line You Red again.
+
+conversation far_right_hall
+on enter FarRightHall
+unless red
+line You Not red.
+";
+
+const EXAMINE: &str = "\
+speaker You
+
+conversation badge
+on examine badge
+set saw_badge
+line You A badge.
+line You Someone dropped it.
+
+conversation badge_again
+on examine badge
+repeat
+line You A badge.
+";
+
+fn start_in(room: Room, dialogue: &str) -> Harness {
+    let mut world = World::with_options(
+        Level::default(),
+        Options {
+            start_room: Some(Room::MainHallFrontOfPod),
+            ..Options::default()
+        },
+    );
+    world.set_dialogue(Dialogue::parse(dialogue).unwrap());
+    let mut h = Harness::with_world(world);
+    if room == Room::FarRightHall {
+        h.hold_until(Action::Right, 12f32, "the far right hall", |w| {
+            w.get_room() == Room::FarRightHall
+        });
+    }
+    h
+}
+
+#[test]
+fn default_dialogue_can_be_read() {
+    let dialogue = match Dialogue::parse(DEFAULT_DIALOGUE) {
+        Ok(dialogue) => dialogue,
+        Err(errors) => panic!("{:?}", errors),
+    };
+    assert_eq!(dialogue, Dialogue::default());
+    assert!(dialogue
+        .find(
+            &Trigger::Solve(PuzzleID::Computer),
+            &HashSet::new(),
+            &Flags::new()
+        )
+        .is_some());
+}
+
+#[test]
+fn every_dialogue_error_is_reported_with_its_line() {
+    let contents = "\
+line You Hello
+speaker You
+conversation a
+on enter Nowhere
+line Nobody Hello
+on solve FarRightHall
+choice red Red.
+conversation a
+conversation b
+line You One
+choice blue Blue.
+choice purple
+";
+    let errors = Dialogue::parse(contents).unwrap_err();
+    let lines: Vec<usize> = errors.iter().map(|e| e.line).collect();
+    // conversations without lines are reported at the end of the file
+    assert_eq!(lines, [1, 4, 5, 7, 8, 12, 12, 12]);
+    assert!(errors[2].message.contains("speaker Nobody is not given"));
+}
+
+#[test]
+fn entering_a_room_starts_its_conversation_once() {
+    let mut h = start_in(Room::MainHallFrontOfPod, DIALOGUE);
+    assert!(!h.world.is_talking());
+    h.world.action_down(Action::Right);
+    h.run_until(6f32, "the window", |w| {
+        w.get_room() == Room::WindowRightHall
+    });
+    assert!(h.world.is_talking());
+    assert_eq!(h.world.get_portrait(), Some("portrait_robot"));
+
+    // the player stops and everything waits until the conversation is over
+    let x = h.world.get_player().x;
+    h.run(10f32);
+    assert_eq!(h.world.get_dialogue(), Some(("Robot", "Hello.")));
+    assert_eq!(h.world.get_player().x, x);
+    h.world.action_down(Action::Left);
+    h.run(1f32);
+    h.world.action_up(Action::Left);
+    assert_eq!(h.world.get_dialogue(), Some(("Robot", "Hello.")));
+    assert_eq!(h.world.get_player().x, x);
+
+    h.tap(Action::Interact);
+    assert_eq!(h.world.get_dialogue(), Some(("You", "")));
+    assert_eq!(h.world.get_portrait(), None);
+    h.talk_through();
+    assert_eq!(h.world.get_dialogue(), None);
+
+    h.hold_until(Action::Left, 6f32, "the main hall", |w| {
+        w.get_room() == Room::MainHallFrontOfPod
+    });
+    h.world.action_down(Action::Right);
+    h.run_until(6f32, "the window", |w| {
+        w.get_room() == Room::WindowRightHall
+    });
+    h.step();
+    assert!(!h.world.is_talking());
+}
+
+#[test]
+fn something_is_used_after_talking_about_it() {
+    let mut h = start_in(Room::FarRightHall, DIALOGUE);
+    h.walk_to_interactable(0);
+    h.tap(Action::Interact);
+    assert_eq!(
+        h.world.get_dialogue().map(|(speaker, _)| speaker),
+        Some("You")
+    );
+    assert_eq!(h.world.get_state(), State::Investigate);
+    h.talk_through();
+    assert_eq!(h.world.get_state(), State::InPuzzle(PuzzleID::FarRightHall));
+}
+
+#[test]
+fn every_trigger_of_the_dialogue_is_in_the_level() {
+    let level = Level::default();
+    assert!(Dialogue::default().get_missing_triggers(&level).is_empty());
+    assert_eq!(level.find_examine("badge"), Some((Room::FarRightHall, 1)));
+
+    let dialogue = Dialogue::parse(
+        "\
+speaker You
+
+conversation far
+on interact FarRightHall 2
+line You Nothing there.
+
+conversation nothing
+on examine nothing
+on interact FarRightHall 1
+line You Nothing there.
+",
+    )
+    .unwrap();
+    assert_eq!(
+        dialogue.get_missing_triggers(&level),
+        [
+            "far is had on using FarRightHall 2, which has 2 things to use",
+            "nothing is had on examining nothing, which the level doesn't have",
+        ]
+    );
+}
+
+#[test]
+fn examining_something_can_be_done_again() {
+    let mut h = start_in(Room::MainHallFrontOfPod, EXAMINE);
+    walk_in(&mut h, Action::Right, Room::WindowRightHall);
+    walk_in(&mut h, Action::Right, Room::FarRightHall);
+    assert_eq!(
+        h.world.get_interactables()[1].get_type(),
+        InteractableType::Examine
+    );
+    h.walk_to_interactable(1);
+    h.tap(Action::Interact);
+    assert!(h.world.get_flags().is_set("saw_badge"));
+    h.tap(Action::Interact);
+    h.tap(Action::Interact);
+    h.run(10f32);
+    assert_eq!(h.world.get_dialogue(), Some(("You", "Someone dropped it.")));
+    h.talk_through();
+
+    for _ in 0..2 {
+        h.tap(Action::Interact);
+        h.run(5f32);
+        assert_eq!(h.world.get_dialogue(), Some(("You", "A badge.")));
+        h.tap(Action::Interact);
+        assert!(!h.world.is_talking());
+        assert_eq!(h.world.get_state(), State::Investigate);
+    }
+}
+
+fn walk_in(h: &mut Harness, action: Action, room: Room) {
+    h.world.action_down(action);
+    h.run_until(6f32, "the next room", |w| w.get_room() == room);
+    h.world.action_up(action);
+    h.step();
+}
+
+fn wait_for_choices(h: &mut Harness) {
+    h.run_until(10f32, "the choices", |w| w.get_choices().is_some());
+}
+
+fn get_choice(h: &Harness) -> usize {
+    h.world.get_choices().unwrap().1
+}
+
+#[test]
+fn choices_are_picked_with_the_keyboard_and_set_flags() {
+    let mut h = start_in(Room::MainHallFrontOfPod, CHOICES);
+    walk_in(&mut h, Action::Right, Room::WindowRightHall);
+    assert!(h.world.get_choices().is_none());
+    wait_for_choices(&mut h);
+    assert_eq!(h.world.get_choices().unwrap().0.len(), 3);
+    assert_eq!(get_choice(&h), 0);
+    h.tap(Action::Up);
+    assert_eq!(get_choice(&h), 2);
+    h.tap(Action::Down);
+    h.tap(Action::Down);
+    assert_eq!(get_choice(&h), 1);
+
+    h.tap(Action::Interact);
+    assert!(h.world.get_flags().is_set("blue"));
+    assert!(!h.world.get_flags().is_set("red"));
+    // nothing is said about blue, so the conversation carries on
+    h.run(5f32);
+    assert_eq!(h.world.get_dialogue(), Some(("Robot", "Green it is.")));
+    h.talk_through();
+
+    walk_in(&mut h, Action::Right, Room::FarRightHall);
+    h.run(5f32);
+    assert_eq!(h.world.get_dialogue(), Some(("You", "Not red.")));
+}
+
+#[test]
+fn a_choice_can_branch_off() {
+    let mut h = start_in(Room::MainHallFrontOfPod, CHOICES);
+    walk_in(&mut h, Action::Right, Room::WindowRightHall);
+    wait_for_choices(&mut h);
+    let rect = dialogue::get_choice_rect(0);
+    let (x, y) = (rect[0] + rect[2] / 2f32, rect[1] + rect[3] / 2f32);
+    // clicking next to the choices doesn't pick one
+    h.world.mouse_down(MouseButton::Left, x, 10f32);
+    h.world.mouse_up(MouseButton::Left, x, 10f32);
+    assert!(h.world.get_choices().is_some());
+    h.world.mouse_down(MouseButton::Left, x, y);
+    h.world.mouse_up(MouseButton::Left, x, y);
+    assert!(h.world.get_flags().is_set("red"));
+
+    h.run(5f32);
+    assert_eq!(h.world.get_dialogue(), Some(("Robot", "Red it is.")));
+    h.talk_through();
+    assert!(!h.world.is_talking());
+
+    walk_in(&mut h, Action::Right, Room::FarRightHall);
+    h.run(5f32);
+    assert_eq!(h.world.get_dialogue(), Some(("You", "Red again.")));
+}
+
+#[test]
+fn saves_can_be_written_out_and_read_back() {
+    let mut save = Save::default();
+    save.flags.set("red");
+    save.flags.set("blue");
+    save.played.insert(String::from("window"));
+    assert_eq!(save.to_text(), "flag blue\nflag red\nplayed window\n");
+    assert_eq!(Save::parse(&save.to_text()), Ok(save.clone()));
+    let errors = Save::parse("flag a\nflags b\n\nplayed c d\n").unwrap_err();
+    let lines: Vec<usize> = errors.iter().map(|e| e.line).collect();
+    assert_eq!(lines, [2, 4]);
+
+    // a world picks up from the flags and conversations it is given, and
+    // what was had isn't had again
+    let mut h = start_in(Room::MainHallFrontOfPod, CHOICES);
+    h.world.load_save(&save);
+    walk_in(&mut h, Action::Right, Room::WindowRightHall);
+    h.run(1f32);
+    assert_eq!(h.world.get_dialogue(), None);
+    walk_in(&mut h, Action::Right, Room::FarRightHall);
+    h.run(5f32);
+    assert_eq!(h.world.get_dialogue(), Some(("You", "Red again.")));
+    assert!(h.events.contains(&Event::Save));
+    assert!(h.world.get_save().played.contains("far_right_hall_red"));
+}
+
+#[test]
+fn a_new_game_has_the_conversations_again() {
+    let mut save = Save::default();
+    save.flags.set("red");
+    save.played.insert(String::from("window"));
+    save.played.insert(String::from("far_right_hall_red"));
+
+    // a fresh world, or one given the cleared save, starts from nothing
+    for loaded in [None, Some(Save::default())] {
+        let mut h = start_in(Room::MainHallFrontOfPod, CHOICES);
+        if let Some(loaded) = &loaded {
+            h.world.load_save(&save);
+            h.world.load_save(loaded);
+        }
+        assert_eq!(h.world.get_save(), Save::default());
+        walk_in(&mut h, Action::Right, Room::WindowRightHall);
+        wait_for_choices(&mut h);
+        assert_eq!(h.world.get_dialogue(), Some(("Robot", "Pick one.")));
+    }
+}
@@ -0,0 +1,333 @@
+mod common;
+
+use common::Harness;
+use ld47_core::input::{Action, MouseButton};
+use ld47_core::interactable::InteractableType;
+use ld47_core::level::Level;
+use ld47_core::player::PLAYER_WIDTH;
+use ld47_core::puzzle::{Puzzle, PuzzleID};
+use ld47_core::world::{Room, State, World, SCREEN_WIDTH};
+
+const SEEDS: u64 = 200;
+const MAX_ROUNDS: u64 = 300;
+const MAX_BURST_STEPS: u64 = 600;
+const ACTIONS: [Action; 7] = [
+    Action::Left,
+    Action::Right,
+    Action::Up,
+    Action::Down,
+    Action::Interact,
+    Action::EnterDoor,
+    Action::Back,
+];
+const FAR_RIGHT_HALL_SOLUTION: [usize; 6] = [0, 1, 4, 5, 6, 8];
+const COMPUTER_SOLUTION: [usize; 6] = [0, 1, 2, 5, 7, 8];
+
+// xorshift, so every failure can be reproduced from its seed
+struct Rng(u64);
+
+impl Rng {
+    fn new(seed: u64) -> Self {
+        Self(seed.wrapping_mul(0x9e37_79b9_7f4a_7c15) | 1)
+    }
+
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn below(&mut self, n: u64) -> u64 {
+        self.next() % n
+    }
+
+    fn range(&mut self, min: f32, max: f32) -> f32 {
+        min + (self.below(10000) as f32 / 10000f32) * (max - min)
+    }
+}
+
+fn check_invariants(world: &World) {
+    let doors = world.get_doors();
+    for interactable in world.get_interactables() {
+        match interactable.get_type() {
+            InteractableType::Door(id) | InteractableType::LockedDoor(id, _) => {
+                assert!(
+                    id < doors.len(),
+                    "{:?} has no door",
+                    interactable.get_type()
+                );
+            }
+            InteractableType::Puzzle(..) | InteractableType::Ship | InteractableType::Examine => (),
+        }
+    }
+    for (idx, door) in doors.iter().enumerate() {
+        assert_eq!(door.get_id(), idx);
+    }
+    if let Some(puzzle) = world.get_puzzle() {
+        assert!(matches!(world.get_state(), State::InPuzzle(_)));
+        assert_eq!(puzzle.get_tiles().len(), 9);
+        if let Some(cursor) = puzzle.get_cursor() {
+            assert!(cursor < 9);
+        }
+    }
+    if world.get_state() == State::Investigate {
+        let x = world.get_player().x;
+        assert!(
+            x >= 0f32 && x + PLAYER_WIDTH <= SCREEN_WIDTH,
+            "player at {}",
+            x
+        );
+    }
+    assert!(world.get_bad_news_state() < 3);
+    let progress = world.get_ending_progress();
+    assert!((0f32..=1f32).contains(&progress));
+}
+
+// anywhere on screen, or right on top of something that can be clicked
+fn random_position(rng: &mut Rng, world: &World) -> (f32, f32) {
+    match rng.below(4) {
+        0 => {
+            let interactables = world.get_interactables();
+            if !interactables.is_empty() {
+                let i = &interactables[rng.below(interactables.len() as u64) as usize];
+                return (i.get_x(), i.get_y());
+            }
+        }
+        1 => {
+            let doors = world.get_doors();
+            if !doors.is_empty() {
+                let d = &doors[rng.below(doors.len() as u64) as usize];
+                return (d.get_center_x(), d.get_center_y());
+            }
+        }
+        2 => {
+            return (
+                250f32 + rng.range(0f32, 300f32),
+                150f32 + rng.range(0f32, 300f32),
+            )
+        }
+        _ => (),
+    }
+    (rng.range(-100f32, 900f32), rng.range(-100f32, 700f32))
+}
+
+fn random_input(h: &mut Harness, rng: &mut Rng) {
+    match rng.below(12) {
+        0..=2 => h
+            .world
+            .action_down(ACTIONS[rng.below(ACTIONS.len() as u64) as usize]),
+        3 | 4 => h
+            .world
+            .action_up(ACTIONS[rng.below(ACTIONS.len() as u64) as usize]),
+        5 | 6 => {
+            let button = match rng.below(3) {
+                0 => MouseButton::Right,
+                1 => MouseButton::Other,
+                _ => MouseButton::Left,
+            };
+            let (x, y) = random_position(rng, &h.world);
+            h.world.mouse_down(button, x, y);
+        }
+        7 => {
+            let (x, y) = random_position(rng, &h.world);
+            h.world.mouse_up(MouseButton::Left, x, y);
+        }
+        8 => h.world.reset_puzzle(),
+        9 if rng.below(20) == 0 => h.world.skip_puzzle(),
+        _ => (),
+    }
+}
+
+fn release_everything(h: &mut Harness) {
+    for action in ACTIONS.iter() {
+        h.world.action_up(*action);
+    }
+    h.world.mouse_up(MouseButton::Left, 0f32, 0f32);
+    h.step();
+}
+
+#[derive(Copy, Clone, PartialEq)]
+enum Move {
+    Left,
+    Right,
+    Door,
+    Use(usize),
+}
+
+// the room layout, walked one step at a time towards the current goal
+fn next_move(world: &World) -> Move {
+    let goal = if !world.is_puzzle_solved(PuzzleID::FarRightHall) {
+        Room::FarRightHall
+    } else if !world.is_puzzle_solved(PuzzleID::Computer) {
+        Room::Computer
+    } else {
+        Room::Final
+    };
+    match (world.get_room(), goal) {
+        (Room::StasisPod, _) => Move::Left,
+        (Room::LeftOfPod, _) => Move::Door,
+        (Room::MainHallFrontOfPod, Room::Computer) => Move::Left,
+        (Room::MainHallFrontOfPod, _) => Move::Right,
+        (Room::WindowRightHall, Room::Computer) => Move::Left,
+        (Room::WindowRightHall, _) => Move::Right,
+        (Room::LeftHall, Room::Computer) => Move::Door,
+        (Room::LeftHall, _) => Move::Right,
+        (Room::FarRightHall, Room::FarRightHall) => Move::Use(0),
+        (Room::FarRightHall, Room::Computer) => Move::Left,
+        (Room::FarRightHall, _) => Move::Right,
+        (Room::Computer, Room::Computer) => Move::Use(1),
+        (Room::Computer, _) => Move::Door,
+        (Room::Final, _) => Move::Use(0),
+    }
+}
+
+fn solve_puzzle(h: &mut Harness, id: PuzzleID) {
+    h.world.reset_puzzle();
+    let solution = match id {
+        PuzzleID::FarRightHall => FAR_RIGHT_HALL_SOLUTION,
+        PuzzleID::Computer => COMPUTER_SOLUTION,
+    };
+    for idx in solution.iter() {
+        h.click(
+            300f32 + (idx % 3) as f32 * 100f32,
+            200f32 + (idx / 3) as f32 * 100f32,
+        );
+    }
+}
+
+fn is_over(world: &World) -> bool {
+    world.get_state() == State::Ending || world.is_dead()
+}
+
+// the bad news may catch up with the player on the way, which also ends the game
+fn walk_to<F: Fn(&World) -> bool>(h: &mut Harness, x: f32, in_range: F) {
+    let action = if h.world.get_player().x + 32f32 > x {
+        Action::Left
+    } else {
+        Action::Right
+    };
+    h.hold_until(action, 5f32, "the player to get in range", |w| {
+        in_range(w) || is_over(w)
+    });
+}
+
+// makes one move towards an ending from wherever the random inputs left off
+fn autopilot_step(h: &mut Harness, seed: u64) {
+    release_everything(h);
+    // the random input may have started looking at something
+    h.talk_through();
+    match h.world.get_state() {
+        State::InPodWakeupText => h.press(Action::Interact),
+        State::InPuzzle(id) => {
+            if h.world.get_puzzle().is_some() {
+                solve_puzzle(h, id);
+            }
+            h.step();
+        }
+        State::Investigate => match next_move(&h.world) {
+            Move::Left => {
+                let room = h.world.get_room();
+                h.hold_until(Action::Left, 6f32, "the left exit", |w| {
+                    w.get_room() != room || is_over(w)
+                });
+            }
+            Move::Right => {
+                let room = h.world.get_room();
+                h.hold_until(Action::Right, 6f32, "the right exit", |w| {
+                    w.get_room() != room || is_over(w)
+                });
+            }
+            Move::Door => {
+                if !h.world.get_doors()[0].get_open() {
+                    walk_to(h, h.world.get_interactables()[0].get_x(), |w| {
+                        w.is_interactable_in_range(&w.get_interactables()[0])
+                    });
+                    if is_over(&h.world) {
+                        return;
+                    }
+                    h.press(Action::Interact);
+                    assert!(
+                        h.world.get_doors()[0].get_open(),
+                        "seed {} can't open the door in {:?}",
+                        seed,
+                        h.world.get_room()
+                    );
+                }
+                walk_to(h, h.world.get_doors()[0].get_center_x(), |w| {
+                    w.is_door_in_range(&w.get_doors()[0])
+                });
+                h.press(Action::EnterDoor);
+            }
+            Move::Use(idx) => {
+                walk_to(h, h.world.get_interactables()[idx].get_x(), |w| {
+                    w.is_interactable_in_range(&w.get_interactables()[idx])
+                });
+                h.press(Action::Interact);
+            }
+        },
+        _ => h.step(),
+    }
+    check_invariants(&h.world);
+}
+
+fn finish_game(h: &mut Harness, seed: u64) {
+    let limit = h.steps + 60 * 60 * 5;
+    while !is_over(&h.world) {
+        assert!(
+            h.steps < limit,
+            "seed {} is stuck in {:?} in {:?}",
+            seed,
+            h.world.get_state(),
+            h.world.get_room()
+        );
+        autopilot_step(h, seed);
+    }
+}
+
+// bursts of random input with some progress in between, so the random input
+// reaches every room and not just the first two
+#[test]
+fn random_input_never_panics_or_softlocks() {
+    for seed in 0..SEEDS {
+        let mut rng = Rng::new(seed);
+        let mut h = Harness::new();
+        for _ in 0..rng.below(MAX_ROUNDS) {
+            if is_over(&h.world) {
+                break;
+            }
+            if rng.below(3) == 0 {
+                for _ in 0..rng.below(MAX_BURST_STEPS) {
+                    if rng.below(4) == 0 {
+                        random_input(&mut h, &mut rng);
+                    }
+                    h.step();
+                    check_invariants(&h.world);
+                }
+            } else {
+                autopilot_step(&mut h, seed);
+            }
+        }
+        finish_game(&mut h, seed);
+    }
+}
+
+#[test]
+fn random_puzzle_input_stays_in_bounds() {
+    for seed in 0..SEEDS {
+        let mut rng = Rng::new(seed);
+        let id = [PuzzleID::FarRightHall, PuzzleID::Computer][(seed & 1) as usize];
+        let mut puzzle = Puzzle::new(id, Level::default().get_puzzle(id).unwrap());
+        for _ in 0..1000 {
+            if rng.below(2) == 0 {
+                puzzle.handle_action(ACTIONS[rng.below(ACTIONS.len() as u64) as usize]);
+            } else {
+                puzzle.handle_click(rng.range(-100f32, 900f32), rng.range(-100f32, 700f32));
+            }
+            assert_eq!(puzzle.get_tiles().len(), 9);
+            if let Some(cursor) = puzzle.get_cursor() {
+                assert!(cursor < 9);
+            }
+        }
+    }
+}
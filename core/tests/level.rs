@@ -0,0 +1,225 @@
+mod common;
+
+use common::Harness;
+use ld47_core::input::Action;
+use ld47_core::interactable::InteractableType;
+use ld47_core::level::{Level, DEFAULT_LEVEL};
+use ld47_core::puzzle::PuzzleID;
+use ld47_core::save::Save;
+use ld47_core::world::{DoorIDs, Event, Options, Room, Sfx, State, World};
+
+#[test]
+fn default_level_has_every_room() {
+    let level = match Level::parse(DEFAULT_LEVEL) {
+        Ok(level) => level,
+        Err(errors) => panic!("{:?}", errors),
+    };
+    for room in Room::ALL.iter() {
+        assert!(level.get_room(*room).is_some(), "{:?} is missing", room);
+    }
+    assert_eq!(level, Level::default());
+}
+
+#[test]
+fn every_error_is_reported_with_its_line() {
+    let contents = "\
+door 100 open
+room StasisPod
+darkness dark
+room LeftOfPod
+door 300 ajar
+door_switch 1 430 450
+room Nowhere
+";
+    let errors = Level::parse(contents).unwrap_err();
+    let lines: Vec<usize> = errors.iter().map(|e| e.line).collect();
+    // the rooms and puzzles that were never given are reported at the end of
+    // the file
+    assert_eq!(lines, [1, 3, 5, 6, 7, 7, 7, 7, 7, 7, 7, 7, 7]);
+    assert!(errors[5]
+        .message
+        .contains("room MainHallFrontOfPod is missing"));
+}
+
+#[test]
+fn puzzles_are_laid_out_by_the_level() {
+    let contents = DEFAULT_LEVEL.replace(
+        "puzzle_tiles Computer 000100000 04 12 24 36 147 25 46 67 48",
+        "puzzle_tiles Computer 111101111 4 4 4 4 4 4 4 4 4",
+    );
+    let mut h = Harness::with_world(World::with_options(
+        Level::parse(&contents).unwrap(),
+        Options {
+            start_room: Some(Room::Computer),
+            ..Options::default()
+        },
+    ));
+    h.walk_to_interactable(1);
+    h.press(Action::Interact);
+    assert_eq!(h.world.get_state(), State::InPuzzle(PuzzleID::Computer));
+    assert_eq!(
+        h.world.get_puzzle().unwrap().get_tiles(),
+        [true, true, true, true, false, true, true, true, true]
+    );
+    // any tile turns over the middle one
+    h.press(Action::Interact);
+    h.step();
+    assert!(h.world.is_puzzle_solved(PuzzleID::Computer));
+
+    let contents = "\
+puzzle_tiles Computer 0001 04 12 24 36 147 25 46 67 48
+puzzle_tiles Computer 000100000 04 12 24 36 147 25 46 67 9
+puzzle_tiles Computer 000100000 04 12 24
+puzzle_tiles Nothing 000100000 04 12 24 36 147 25 46 67 48
+";
+    let errors = Level::parse(contents).unwrap_err();
+    let lines: Vec<usize> = errors.iter().take(4).map(|e| e.line).collect();
+    assert_eq!(lines, [1, 2, 3, 4]);
+    assert!(errors
+        .iter()
+        .any(|e| e.message == "the tiles of Computer are missing"));
+}
+
+#[test]
+fn entries_can_only_be_used_with_their_flag() {
+    let contents = DEFAULT_LEVEL.replace(
+        "puzzle Computer 300 400 200",
+        "puzzle Computer 300 400 200 if got_password",
+    );
+    let level = Level::parse(&contents).unwrap();
+    let condition = &level.get_room(Room::Computer).unwrap().interactables[1].condition;
+    assert_eq!(*condition, Some((String::from("got_password"), true)));
+    let mut h = Harness::with_world(World::with_options(
+        level,
+        Options {
+            start_room: Some(Room::Computer),
+            ..Options::default()
+        },
+    ));
+    h.walk_to_interactable(1);
+    h.press(Action::Interact);
+    assert_eq!(h.world.get_state(), State::Investigate);
+    assert!(h.events.contains(&Event::PlaySfx(Sfx::Error)));
+
+    let mut save = Save::default();
+    save.flags.set("got_password");
+    h.world.load_save(&save);
+    h.press(Action::Interact);
+    assert_eq!(h.world.get_state(), State::InPuzzle(PuzzleID::Computer));
+
+    let errors = Level::parse("room Computer\ndarkness 0 if got_password\n").unwrap_err();
+    assert_eq!(errors[0].line, 2);
+    assert_eq!(errors[0].message, "darkness can't have a condition");
+}
+
+#[test]
+fn changing_the_level_lays_out_the_current_room_again() {
+    let mut h = Harness::new();
+    h.run_until(10f32, "the wakeup text", |w| {
+        w.get_state() == State::InPodWakeupText
+    });
+    // nothing is laid out before the player is out of the pod
+    h.world.set_level(Level::default());
+    assert_eq!(h.world.get_state(), State::InPodWakeupText);
+    while h.world.get_state() != State::Investigate {
+        h.press(Action::Interact);
+    }
+    h.hold_until(Action::Left, 6f32, "the left exit", |w| {
+        w.get_room() == Room::LeftOfPod
+    });
+    h.walk_to_interactable(0);
+    h.press(Action::Interact);
+    assert!(h.world.get_doors()[0].get_open());
+
+    let contents = DEFAULT_LEVEL.replace("door 300 closed", "door 200 closed");
+    h.world.set_level(Level::parse(&contents).unwrap());
+    assert_eq!(h.world.get_room(), Room::LeftOfPod);
+    assert_eq!(h.world.get_doors()[0].get_x(), 200f32);
+    // the door is still open, since that is kept apart from the level
+    assert!(h.world.get_doors()[0].get_open());
+    assert_eq!(
+        h.world.get_door_state(DoorIDs::LeftOfPod),
+        Some((true, true))
+    );
+    assert_eq!(
+        h.world.get_interactables()[0].get_type(),
+        InteractableType::Door(0)
+    );
+}
+
+#[test]
+fn ambience_follows_the_room_and_the_escape() {
+    let sounds = |w: &World| -> Vec<String> {
+        w.get_ambient()
+            .iter()
+            .map(|ambient| ambient.sound.clone())
+            .collect()
+    };
+    let mut h = Harness::with_world(World::with_options(
+        Level::default(),
+        Options {
+            start_room: Some(Room::Computer),
+            ..Options::default()
+        },
+    ));
+    h.step();
+    assert_eq!(sounds(&h.world), ["ambient_hum"]);
+
+    h.walk_to_interactable(1);
+    h.press(Action::Interact);
+    h.world.skip_puzzle();
+    h.run_until(1f32, "leaving the puzzle", |w| {
+        w.get_state() == State::Investigate
+    });
+    assert!(h.world.is_end_game());
+    assert_eq!(sounds(&h.world), ["ambient_hum", "ambient_alarm"]);
+
+    let level = Level::default();
+    assert_eq!(
+        level.get_ambient_sounds(),
+        ["ambient_alarm", "ambient_hum", "ambient_wind"]
+    );
+    let errors =
+        Level::parse(&DEFAULT_LEVEL.replace("ambient_wind 0.5", "ambient_wind loud")).unwrap_err();
+    assert!(errors[0].message.contains("\"loud\" is not a number"));
+}
+
+#[test]
+fn exits_and_doors_lead_where_the_level_says() {
+    let contents = DEFAULT_LEVEL.replace("exit left LeftOfPod", "exit left Computer");
+    let mut h = Harness::new();
+    h.world.set_level(Level::parse(&contents).unwrap());
+    h.run_until(10f32, "the wakeup text", |w| {
+        w.get_state() == State::InPodWakeupText
+    });
+    while h.world.get_state() != State::Investigate {
+        h.press(Action::Interact);
+    }
+    h.hold_until(Action::Left, 6f32, "the left exit", |w| {
+        w.get_room() == Room::Computer
+    });
+
+    h.walk_to_door(0);
+    h.press(Action::EnterDoor);
+    assert_eq!(h.world.get_state(), State::EnterDoor(Room::LeftHall));
+    h.run_until(3f32, "going through the door", |w| {
+        w.get_state() == State::Investigate
+    });
+    assert_eq!(h.world.get_room(), Room::LeftHall);
+    // out through the door that leads back
+    assert!(h.world.is_door_in_range(&h.world.get_doors()[0]));
+}
+
+#[test]
+fn exits_are_checked() {
+    let contents = "room StasisPod\nexit up LeftOfPod\nexit left Nowhere\nexit left LeftOfPod\nexit left LeftOfPod\nexit right Final later\n";
+    let errors = Level::parse(contents).unwrap_err();
+    assert_eq!(errors[0].line, 2);
+    assert_eq!(errors[0].message, "an exit is left or right, not \"up\"");
+    assert_eq!(errors[1].line, 3);
+    assert_eq!(errors[1].message, "invalid room \"Nowhere\"");
+    assert_eq!(errors[2].line, 5);
+    assert_eq!(errors[2].message, "the left exit is given twice");
+    assert_eq!(errors[3].line, 6);
+    assert_eq!(errors[3].message, "invalid exit condition \"later\"");
+}
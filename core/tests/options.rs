@@ -0,0 +1,155 @@
+mod common;
+
+use common::Harness;
+use ld47_core::input::Action;
+use ld47_core::level::Level;
+use ld47_core::puzzle::{Puzzle, PuzzleID, PuzzleLayout};
+use ld47_core::world::{Difficulty, Event, Music, Options, Room, State, World};
+
+const IDS: [PuzzleID; 2] = [PuzzleID::FarRightHall, PuzzleID::Computer];
+
+fn start_in(room: Room, difficulty: Difficulty) -> Harness {
+    Harness::with_world(World::with_options(
+        Level::default(),
+        Options {
+            difficulty,
+            start_room: Some(room),
+            ..Options::default()
+        },
+    ))
+}
+
+fn get_layout(id: PuzzleID) -> PuzzleLayout {
+    Level::default().get_puzzle(id).unwrap().clone()
+}
+
+fn click_tile(puzzle: &mut Puzzle, idx: usize) {
+    puzzle.handle_click(
+        300f32 + (idx % 3) as f32 * 100f32,
+        200f32 + (idx / 3) as f32 * 100f32,
+    );
+}
+
+// pressing a tile twice undoes it, so trying every set of tiles once is enough
+fn is_solvable(id: PuzzleID, seed: u64) -> bool {
+    (0..512u32).any(|presses| {
+        let mut puzzle = Puzzle::with_seed(id, &get_layout(id), seed);
+        for idx in 0..9 {
+            if presses & (1 << idx) != 0 {
+                click_tile(&mut puzzle, idx);
+            }
+        }
+        puzzle.is_solved()
+    })
+}
+
+#[test]
+fn seeded_puzzles_are_scrambled_the_same_way_every_time() {
+    for seed in 0..100 {
+        for id in IDS.iter() {
+            let mut puzzle = Puzzle::with_seed(*id, &get_layout(*id), seed);
+            assert!(!puzzle.is_solved(), "seed {} starts solved", seed);
+            assert_eq!(
+                puzzle.get_tiles(),
+                Puzzle::with_seed(*id, &get_layout(*id), seed).get_tiles()
+            );
+            assert!(is_solvable(*id, seed), "seed {} can't be solved", seed);
+
+            let start = puzzle.get_tiles().to_vec();
+            click_tile(&mut puzzle, 4);
+            puzzle.reset();
+            assert_eq!(puzzle.get_tiles(), &start[..]);
+        }
+    }
+}
+
+#[test]
+fn skipping_the_intro_starts_outside_the_pod() {
+    let world = World::with_options(
+        Level::default(),
+        Options {
+            skip_intro: true,
+            ..Options::default()
+        },
+    );
+    let mut h = Harness::with_world(world);
+    h.step();
+    assert_eq!(h.world.get_state(), State::Investigate);
+    assert_eq!(h.world.get_room(), Room::StasisPod);
+    assert_eq!(
+        h.world.get_text(),
+        "A and D or Left and Right or Left Click to move"
+    );
+    assert!(h.saw_event(Event::PlayMusic(Music::Main)));
+}
+
+#[test]
+fn the_ship_can_still_be_reached_on_hard() {
+    let mut h = start_in(Room::Computer, Difficulty::Hard);
+    h.walk_to_interactable(1);
+    h.press(Action::Interact);
+    h.world.skip_puzzle();
+    h.run_until(1f32, "leaving the puzzle", |w| {
+        w.get_state() == State::Investigate
+    });
+    assert!(h.world.is_end_game());
+
+    h.walk_to_door(0);
+    h.press(Action::EnterDoor);
+    h.run_until(3f32, "going through the door", |w| {
+        w.get_state() == State::Investigate
+    });
+    assert_eq!(h.world.get_room(), Room::LeftHall);
+    for room in [
+        Room::MainHallFrontOfPod,
+        Room::WindowRightHall,
+        Room::FarRightHall,
+        Room::Final,
+    ]
+    .iter()
+    {
+        h.hold_until(Action::Right, 6f32, "the right exit", |w| {
+            w.get_room() == *room
+        });
+    }
+    h.walk_to_interactable(0);
+    h.press(Action::Interact);
+    assert_eq!(h.world.get_state(), State::Ending);
+    assert!(!h.world.is_dead());
+}
+
+#[test]
+fn the_bad_news_spreads_slower_on_easy() {
+    let mut offsets = Vec::new();
+    for difficulty in [Difficulty::Easy, Difficulty::Normal, Difficulty::Hard].iter() {
+        let mut h = start_in(Room::Computer, *difficulty);
+        h.walk_to_interactable(1);
+        h.press(Action::Interact);
+        h.world.skip_puzzle();
+        h.run(5f32);
+        offsets.push(h.world.get_bad_news_xoffset());
+    }
+    assert!(offsets[0] < offsets[1]);
+    assert!(offsets[1] < offsets[2]);
+}
+
+#[test]
+fn faster_text_types_out_sooner() {
+    let mut chars = Vec::new();
+    for text_speed in [0.5f32, 1f32, 2f32].iter() {
+        let mut h = Harness::with_world(World::with_options(
+            Level::default(),
+            Options {
+                text_speed: *text_speed,
+                ..Options::default()
+            },
+        ));
+        h.run_until(10f32, "the wake up text", |w| {
+            w.get_state() == State::InPodWakeupText
+        });
+        h.run(2f32);
+        chars.push(h.world.get_text().len());
+    }
+    assert!(chars[0] < chars[1]);
+    assert!(chars[1] < chars[2]);
+}
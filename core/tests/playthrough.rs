@@ -0,0 +1,314 @@
+mod common;
+
+use common::Harness;
+use ld47_core::input::Action;
+use ld47_core::interactable::InteractableType;
+use ld47_core::puzzle::PuzzleID;
+use ld47_core::world::{DoorIDs, Event, Music, Room, Sfx, State};
+
+// tiles that have to be toggled to turn every tile green
+const FAR_RIGHT_HALL_SOLUTION: [usize; 6] = [0, 1, 4, 5, 6, 8];
+const COMPUTER_SOLUTION: [usize; 6] = [0, 1, 2, 5, 7, 8];
+
+fn wake_up(h: &mut Harness) {
+    h.run_until(10f32, "the wakeup text", |w| {
+        w.get_state() == State::InPodWakeupText
+    });
+    assert_eq!(h.world.get_room(), Room::StasisPod);
+
+    // a tap shows the whole text, and the next ones go on once it is done
+    h.press(Action::Interact);
+    assert_eq!(h.world.get_text(), "What.. Where am I?..");
+    for _ in 0..2 {
+        if h.world.get_state() != State::InPodWakeupText {
+            break;
+        }
+        h.press(Action::Interact);
+    }
+    assert_eq!(h.world.get_state(), State::GetOutOfPod);
+
+    h.run_until(5f32, "getting out of the pod", |w| {
+        w.get_state() == State::Investigate
+    });
+    assert!(h.saw_event(Event::PlayMusic(Music::Main)));
+}
+
+fn walk_out_left(h: &mut Harness, to: Room) {
+    h.hold_until(Action::Left, 6f32, "the left exit", |w| w.get_room() == to);
+    assert_eq!(h.world.get_state(), State::Investigate);
+}
+
+fn walk_out_right(h: &mut Harness, to: Room) {
+    h.hold_until(Action::Right, 6f32, "the right exit", |w| {
+        w.get_room() == to
+    });
+    assert_eq!(h.world.get_state(), State::Investigate);
+}
+
+fn enter_door(h: &mut Harness, to: Room) {
+    h.walk_to_door(0);
+    h.press(Action::EnterDoor);
+    assert_eq!(h.world.get_state(), State::EnterDoor(to));
+    h.run_until(3f32, "going through the door", |w| {
+        w.get_state() == State::Investigate
+    });
+    assert_eq!(h.world.get_room(), to);
+    assert_eq!(h.world.get_player().opacity, 1f32);
+}
+
+fn click_tile(h: &mut Harness, idx: usize) {
+    h.click(
+        300f32 + (idx % 3) as f32 * 100f32,
+        200f32 + (idx / 3) as f32 * 100f32,
+    );
+}
+
+fn press_tile(h: &mut Harness, idx: usize) {
+    let cursor = h.world.get_puzzle().unwrap().get_cursor().unwrap();
+    for _ in 0..(idx % 3 + 3 - cursor % 3) % 3 {
+        h.press(Action::Right);
+    }
+    for _ in 0..(idx / 3 + 3 - cursor / 3) % 3 {
+        h.press(Action::Down);
+    }
+    assert_eq!(h.world.get_puzzle().unwrap().get_cursor(), Some(idx));
+    h.press(Action::Interact);
+}
+
+fn interactable_type(h: &Harness, idx: usize) -> InteractableType {
+    h.world.get_interactables()[idx].get_type()
+}
+
+#[test]
+fn full_playthrough() {
+    let mut h = Harness::new();
+    wake_up(&mut h);
+
+    // the pod room only leads left, to the door into the main hall
+    assert_eq!(h.world.get_room_exits(), (true, false));
+    walk_out_left(&mut h, Room::LeftOfPod);
+    assert_eq!(h.world.get_door_state(DoorIDs::LeftOfPod), None);
+    assert!(!h.world.get_doors()[0].get_open());
+
+    h.walk_to_interactable(0);
+    h.press(Action::Interact);
+    assert_eq!(
+        h.world.get_door_state(DoorIDs::LeftOfPod),
+        Some((true, true))
+    );
+    assert!(h.world.get_doors()[0].get_open());
+    // the switch is away from the door, the sound comes from the door
+    let door_x = h.world.get_doors()[0].get_center_x();
+    assert!(h.saw_event(Event::PlaySfxAt(Sfx::Door, door_x)));
+
+    enter_door(&mut h, Room::MainHallFrontOfPod);
+    assert!(h.world.get_doors()[0].get_open());
+    assert_eq!(
+        interactable_type(&h, 0),
+        InteractableType::LockedDoor(0, true)
+    );
+
+    walk_out_right(&mut h, Room::WindowRightHall);
+    assert!(h.saw_event(Event::PlayStinger(Music::Discovery)));
+    assert!(!h.saw_event(Event::StopMusic(Music::Main)));
+    walk_out_right(&mut h, Room::FarRightHall);
+    assert_eq!(h.world.get_room_exits(), (true, false));
+
+    // the far right hall puzzle, solved with the mouse
+    h.walk_to_interactable(0);
+    h.press(Action::Interact);
+    assert_eq!(h.world.get_state(), State::InPuzzle(PuzzleID::FarRightHall));
+    for idx in FAR_RIGHT_HALL_SOLUTION.iter() {
+        assert!(!h.world.get_puzzle().unwrap().is_solved());
+        click_tile(&mut h, *idx);
+    }
+    h.run_until(1f32, "leaving the puzzle", |w| {
+        w.get_state() == State::Investigate
+    });
+    assert!(h.world.get_puzzle().is_none());
+    assert!(h.world.is_puzzle_solved(PuzzleID::FarRightHall));
+    assert!(!h.world.is_puzzle_solved(PuzzleID::Computer));
+    assert!(h.saw_event(Event::PlaySfx(Sfx::Success)));
+    assert_eq!(
+        interactable_type(&h, 0),
+        InteractableType::Puzzle(PuzzleID::FarRightHall, true)
+    );
+    // solving it unlocks the left hall and shuts the way back to the pod
+    assert_eq!(
+        h.world.get_door_state(DoorIDs::LeftHall),
+        Some((false, true))
+    );
+    assert_eq!(
+        h.world.get_door_state(DoorIDs::LeftOfPod),
+        Some((false, false))
+    );
+
+    // a solved puzzle can't be entered again
+    h.press(Action::Interact);
+    assert_eq!(h.world.get_state(), State::Investigate);
+
+    walk_out_left(&mut h, Room::WindowRightHall);
+    walk_out_left(&mut h, Room::MainHallFrontOfPod);
+    assert!(!h.world.get_doors()[0].get_open());
+    assert_eq!(
+        interactable_type(&h, 0),
+        InteractableType::LockedDoor(0, false)
+    );
+    h.walk_to_interactable(0);
+    h.events.clear();
+    h.press(Action::Interact);
+    assert!(h.saw_event(Event::PlaySfx(Sfx::Error)));
+    assert!(!h.world.get_doors()[0].get_open());
+
+    walk_out_left(&mut h, Room::LeftHall);
+    assert_eq!(
+        interactable_type(&h, 0),
+        InteractableType::LockedDoor(0, true)
+    );
+    assert!(!h.world.get_doors()[0].get_open());
+    h.walk_to_interactable(0);
+    h.press(Action::Interact);
+    assert_eq!(
+        h.world.get_door_state(DoorIDs::LeftHall),
+        Some((true, true))
+    );
+    // opening the left hall door must not undo the lock on the pod door
+    assert_eq!(
+        h.world.get_door_state(DoorIDs::LeftOfPod),
+        Some((false, false))
+    );
+
+    enter_door(&mut h, Room::Computer);
+    assert!(h.world.get_doors()[0].get_open());
+
+    // the computer puzzle, solved with the keyboard
+    h.walk_to_interactable(1);
+    h.press(Action::Interact);
+    assert_eq!(h.world.get_state(), State::InPuzzle(PuzzleID::Computer));
+    assert_eq!(h.world.get_puzzle().unwrap().get_cursor(), Some(0));
+    for idx in COMPUTER_SOLUTION.iter() {
+        assert!(!h.world.get_puzzle().unwrap().is_solved());
+        press_tile(&mut h, *idx);
+    }
+    h.run_until(1f32, "leaving the puzzle", |w| {
+        w.get_state() == State::Investigate
+    });
+    assert!(h.world.is_puzzle_solved(PuzzleID::Computer));
+    assert!(h.world.is_end_game());
+    assert!(h.saw_event(Event::PlayMusic(Music::BadNews)));
+    assert_eq!(
+        interactable_type(&h, 1),
+        InteractableType::Puzzle(PuzzleID::Computer, true)
+    );
+    assert_eq!(
+        h.world.get_door_state(DoorIDs::LeftHall),
+        Some((true, true))
+    );
+
+    enter_door(&mut h, Room::LeftHall);
+    walk_out_right(&mut h, Room::MainHallFrontOfPod);
+    walk_out_right(&mut h, Room::WindowRightHall);
+    walk_out_right(&mut h, Room::FarRightHall);
+    assert_eq!(h.world.get_room_exits(), (true, true));
+    walk_out_right(&mut h, Room::Final);
+    assert!(!h.world.is_dead());
+
+    h.walk_to_interactable(0);
+    assert_eq!(interactable_type(&h, 0), InteractableType::Ship);
+    h.press(Action::Interact);
+    assert_eq!(h.world.get_state(), State::Ending);
+    assert!(h.saw_event(Event::PlayMusic(Music::Ending)));
+    assert!(h.saw_event(Event::ClearSave));
+
+    h.run_until(20f32, "the ship to arrive", |w| {
+        w.get_ending_progress() >= 1f32
+    });
+    assert_eq!(h.world.get_state(), State::Ending);
+    assert!(!h.world.is_dead());
+}
+
+#[test]
+fn puzzle_can_be_left_and_reentered() {
+    let mut h = Harness::new();
+    wake_up(&mut h);
+    walk_out_left(&mut h, Room::LeftOfPod);
+    h.walk_to_interactable(0);
+    h.press(Action::Interact);
+    enter_door(&mut h, Room::MainHallFrontOfPod);
+    walk_out_right(&mut h, Room::WindowRightHall);
+    walk_out_right(&mut h, Room::FarRightHall);
+
+    h.walk_to_interactable(0);
+    h.press(Action::Interact);
+    click_tile(&mut h, 4);
+    h.press(Action::Back);
+    h.run_until(1f32, "leaving the puzzle", |w| {
+        w.get_state() == State::Investigate
+    });
+    assert!(!h.world.is_puzzle_solved(PuzzleID::FarRightHall));
+    // leaving without solving leaves every door as it was
+    assert_eq!(h.world.get_door_state(DoorIDs::LeftHall), None);
+    assert_eq!(
+        h.world.get_door_state(DoorIDs::LeftOfPod),
+        Some((true, true))
+    );
+
+    h.press(Action::Interact);
+    assert_eq!(h.world.get_state(), State::InPuzzle(PuzzleID::FarRightHall));
+    h.world.skip_puzzle();
+    h.run_until(1f32, "leaving the puzzle", |w| {
+        w.get_state() == State::Investigate
+    });
+    assert!(h.world.is_puzzle_solved(PuzzleID::FarRightHall));
+}
+
+#[test]
+fn bad_news_catches_up_eventually() {
+    let mut h = Harness::new();
+    wake_up(&mut h);
+    walk_out_left(&mut h, Room::LeftOfPod);
+    h.walk_to_interactable(0);
+    h.press(Action::Interact);
+    enter_door(&mut h, Room::MainHallFrontOfPod);
+    walk_out_left(&mut h, Room::LeftHall);
+    // the left hall door stays locked until the far right hall puzzle is done
+    assert_eq!(
+        interactable_type(&h, 0),
+        InteractableType::LockedDoor(0, false)
+    );
+    h.walk_to_interactable(0);
+    h.press(Action::Interact);
+    assert!(h.saw_event(Event::PlaySfx(Sfx::Error)));
+    assert!(!h.world.is_end_game());
+
+    walk_out_right(&mut h, Room::MainHallFrontOfPod);
+    walk_out_right(&mut h, Room::WindowRightHall);
+    walk_out_right(&mut h, Room::FarRightHall);
+    h.walk_to_interactable(0);
+    h.press(Action::Interact);
+    h.world.skip_puzzle();
+    h.run(1f32);
+    walk_out_left(&mut h, Room::WindowRightHall);
+    walk_out_left(&mut h, Room::MainHallFrontOfPod);
+    walk_out_left(&mut h, Room::LeftHall);
+    h.walk_to_interactable(0);
+    h.press(Action::Interact);
+    enter_door(&mut h, Room::Computer);
+    h.walk_to_interactable(1);
+    h.press(Action::Interact);
+    h.world.skip_puzzle();
+    h.run(1f32);
+    assert!(h.world.is_end_game());
+    // nothing moves while the computer is talking
+    let offset = h.world.get_bad_news_xoffset();
+    h.run(5f32);
+    assert!(h.world.is_talking());
+    assert_eq!(h.world.get_bad_news_xoffset(), offset);
+    h.talk_through();
+
+    // standing still lets the bad news reach the player
+    h.run_until(60f32, "the bad news", |w| w.is_dead());
+    let offset = h.world.get_bad_news_xoffset();
+    h.run(1f32);
+    assert_eq!(h.world.get_bad_news_xoffset(), offset);
+}
@@ -0,0 +1,69 @@
+use ld47_core::script::{self, Script};
+use ld47_core::strings::Strings;
+
+#[test]
+fn characters_are_given_their_script() {
+    assert_eq!(Script::of('a'), Script::Latin);
+    assert_eq!(Script::of('ß'), Script::Latin);
+    assert_eq!(Script::of('Ж'), Script::Cyrillic);
+    assert_eq!(Script::of('λ'), Script::Greek);
+    assert_eq!(Script::of('あ'), Script::Cjk);
+    assert_eq!(Script::of('地'), Script::Cjk);
+    assert_eq!(Script::of('한'), Script::Hangul);
+    assert_eq!(Script::of(' '), Script::Common);
+    assert_eq!(Script::of('?'), Script::Common);
+    assert_eq!(Script::of('7'), Script::Common);
+    for script in Script::ALL.iter() {
+        assert_eq!(Script::from_name(script.get_name()), Some(*script));
+    }
+}
+
+#[test]
+fn text_is_split_where_the_script_changes() {
+    assert_eq!(script::split(""), Vec::new());
+    assert_eq!(script::split("42%"), [(Script::Common, "42%")]);
+    assert_eq!(
+        script::split("Was.. Wo bin ich?.."),
+        [(Script::Latin, "Was.. Wo bin ich?..")]
+    );
+    // punctuation stays with what is before it, or after it at the start
+    assert_eq!(
+        script::split("[E] ボタン: Interact"),
+        [
+            (Script::Latin, "[E] "),
+            (Script::Cjk, "ボタン: "),
+            (Script::Latin, "Interact")
+        ]
+    );
+    assert_eq!(
+        script::split("1. Привет world"),
+        [(Script::Cyrillic, "1. Привет "), (Script::Latin, "world")]
+    );
+}
+
+#[test]
+fn each_run_is_drawn_with_the_font_for_its_script() {
+    let strings =
+        Strings::parse("font.default main\nfont.cjk noto_cjk\nfont.hangul noto_kr\n").unwrap();
+    let fonts: Vec<(&str, &str)> = script::split("[E] ボタン: 한국어 Interact")
+        .into_iter()
+        .map(|(script, run)| (run, script::get_font_name(&strings, script).unwrap()))
+        .collect();
+    assert_eq!(
+        fonts,
+        [
+            ("[E] ", "main"),
+            ("ボタン: ", "noto_cjk"),
+            ("한국어 ", "noto_kr"),
+            ("Interact", "main")
+        ]
+    );
+    assert_eq!(
+        script::get_font_name(&Strings::default(), Script::Cjk),
+        Some("main")
+    );
+    assert_eq!(
+        script::get_font_name(&Strings::parse("").unwrap(), Script::Latin),
+        None
+    );
+}
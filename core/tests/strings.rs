@@ -0,0 +1,113 @@
+mod common;
+
+use common::Harness;
+use ld47_core::dialogue::Dialogue;
+use ld47_core::input::Action;
+use ld47_core::level::Level;
+use ld47_core::strings::{Strings, DEFAULT_STRINGS};
+use ld47_core::world::{Options, Room, World};
+
+const GERMAN: &str = include_str!("../../resources/lang/de.txt");
+
+fn start_in(room: Room, strings: Strings) -> Harness {
+    let mut world = World::with_options(
+        Level::default(),
+        Options {
+            start_room: Some(room),
+            ..Options::default()
+        },
+    );
+    world.set_strings(strings);
+    Harness::with_world(world)
+}
+
+#[test]
+fn every_id_used_by_the_game_is_in_english() {
+    let strings = match Strings::parse(DEFAULT_STRINGS) {
+        Ok(strings) => strings,
+        Err(errors) => panic!("{:?}", errors),
+    };
+    assert_eq!(strings, Strings::default());
+
+    let level = Level::default();
+    let mut texts: Vec<&str> = Room::ALL
+        .iter()
+        .filter_map(|room| level.get_room(*room)?.text.as_deref())
+        .collect();
+    let dialogue = Dialogue::default();
+    let mut idx = 0;
+    while let Some(conversation) = dialogue.get_conversation(idx) {
+        for line in &conversation.lines {
+            texts.push(&line.text);
+            texts.extend(line.choices.iter().map(|choice| choice.text.as_str()));
+        }
+        idx += 1;
+    }
+    for text in texts {
+        if let Some(id) = text.strip_prefix('@') {
+            assert!(strings.find(id).is_some(), "{} is not in English", id);
+        }
+    }
+    assert!(strings.find("game.wake_up").is_some());
+    assert_eq!(strings.find("font.default"), Some("main"));
+}
+
+#[test]
+fn german_only_has_ids_english_has() {
+    let german = match Strings::parse(GERMAN) {
+        Ok(strings) => strings,
+        Err(errors) => panic!("{:?}", errors),
+    };
+    assert_eq!(Strings::default().get_missing(&german), Vec::<&str>::new());
+}
+
+#[test]
+fn missing_ids_fall_back_to_english() {
+    let strings = Strings::parse("language Test\nmenu.mods Modifikationen\n")
+        .unwrap()
+        .with_fallback(&Strings::default());
+    assert_eq!(strings.get("language"), "Test");
+    assert_eq!(strings.get("menu.mods"), "Modifikationen");
+    assert_eq!(strings.get("menu.settings"), "Settings");
+    assert_eq!(
+        strings.get("game.interact"),
+        "[E] or Left Click\nto Interact"
+    );
+    // ids nobody gives are shown as they are
+    assert_eq!(strings.get("no.such.id"), "no.such.id");
+    assert_eq!(strings.resolve("@menu.mods"), "Modifikationen");
+    assert_eq!(strings.resolve("menu.mods"), "menu.mods");
+}
+
+#[test]
+fn string_errors_are_reported_by_line() {
+    let contents = "\
+# a comment
+menu.mods Mods
+menu.back
+menu.mods Mods again
+";
+    let errors = Strings::parse(contents).unwrap_err();
+    let lines: Vec<usize> = errors.iter().map(|e| e.line).collect();
+    assert_eq!(lines, [3, 4]);
+    assert!(errors[1].message.contains("menu.mods is given twice"));
+}
+
+#[test]
+fn the_world_talks_in_the_language_it_is_given() {
+    let german = Strings::parse(GERMAN)
+        .unwrap()
+        .with_fallback(&Strings::default());
+    let mut h = start_in(Room::StasisPod, german.clone());
+    h.step();
+    assert_eq!(
+        h.world.get_text(),
+        "A und D oder Links und Rechts oder Linksklick zum Bewegen"
+    );
+
+    let mut h = start_in(Room::MainHallFrontOfPod, german);
+    h.world.action_down(Action::Right);
+    h.run_until(6f32, "the window", |w| w.is_talking());
+    h.run(10f32);
+    assert_eq!(h.world.get_dialogue(), Some(("Du", "Ist das... die Erde?")));
+}
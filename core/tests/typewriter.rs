@@ -0,0 +1,131 @@
+mod common;
+
+use common::{Harness, DT};
+use ld47_core::input::{Action, MouseButton};
+use ld47_core::typewriter::{Typewriter, INSTANT, TAP_TIME, TEXT_RATE};
+use ld47_core::world::{Event, Sfx, State};
+
+const LINE: &str = "What.. Where am I?..";
+
+fn type_for(typewriter: &mut Typewriter, seconds: f32) -> usize {
+    let mut sounds = 0;
+    for _ in 0..(seconds / DT) as usize {
+        if typewriter.update(DT) {
+            sounds += 1;
+        }
+    }
+    sounds
+}
+
+#[test]
+fn holding_down_fast_forwards() {
+    let mut typewriter = Typewriter::new(1f32);
+    typewriter.start(LINE);
+    type_for(&mut typewriter, 2f32);
+    let normal = typewriter.get_text().len();
+
+    let mut typewriter = Typewriter::new(1f32);
+    typewriter.start(LINE);
+    typewriter.press();
+    type_for(&mut typewriter, 2f32);
+    let fast = typewriter.get_text().len();
+    assert!(typewriter.is_typing());
+    assert!(normal < fast, "{} chars normally, {} held", normal, fast);
+
+    // letting go after holding goes back to the usual speed
+    typewriter.release();
+    assert!(typewriter.is_typing());
+    type_for(&mut typewriter, TEXT_RATE * 2f32);
+    let len = typewriter.get_text().len();
+    assert!(
+        len > fast && len <= fast + 2,
+        "{} chars after {}",
+        len,
+        fast
+    );
+}
+
+#[test]
+fn tapping_finishes_the_line() {
+    let mut typewriter = Typewriter::new(1f32);
+    typewriter.start(LINE);
+    type_for(&mut typewriter, 1f32);
+    typewriter.press();
+    type_for(&mut typewriter, TAP_TIME / 2f32);
+    typewriter.release();
+    assert!(!typewriter.is_typing());
+    assert_eq!(typewriter.get_text(), LINE);
+}
+
+#[test]
+fn instant_text_is_never_typed() {
+    let mut typewriter = Typewriter::new(INSTANT);
+    typewriter.start(LINE);
+    assert!(!typewriter.is_typing());
+    assert_eq!(typewriter.get_text(), LINE);
+    assert_eq!(type_for(&mut typewriter, 1f32), 0);
+}
+
+#[test]
+fn the_typing_sound_is_throttled() {
+    let mut typewriter = Typewriter::new(1f32);
+    typewriter.start(LINE);
+    let sounds = type_for(&mut typewriter, 10f32);
+    assert_eq!(sounds, LINE.len());
+
+    // a character every frame would be far too many sounds
+    let mut typewriter = Typewriter::new(20f32);
+    typewriter.start(LINE);
+    let sounds = type_for(&mut typewriter, 10f32);
+    assert_eq!(typewriter.get_text(), LINE);
+    assert!(sounds < LINE.len() / 2, "{} sounds", sounds);
+}
+
+#[test]
+fn tapping_through_the_wake_up_text() {
+    let mut h = Harness::new();
+    h.run_until(10f32, "the wake up text", |w| {
+        w.get_state() == State::InPodWakeupText
+    });
+    h.run(1f32);
+    assert!(h.saw_event(Event::PlaySfx(Sfx::Text)));
+    assert_ne!(h.world.get_text(), LINE);
+
+    h.press(Action::Interact);
+    assert_eq!(h.world.get_state(), State::InPodWakeupText);
+    assert_eq!(h.world.get_text(), LINE);
+    h.press(Action::Interact);
+    h.press(Action::Interact);
+    assert_eq!(h.world.get_state(), State::GetOutOfPod);
+}
+
+#[test]
+fn only_letting_go_of_what_held_the_text_lets_it_go() {
+    let mut h = Harness::new();
+    h.run_until(10f32, "the wake up text", |w| {
+        w.get_state() == State::InPodWakeupText
+    });
+    h.run(1f32);
+    let typed = h.world.get_text().len();
+
+    // a quick tap of something else while holding doesn't finish the line
+    h.world.action_down(Action::Interact);
+    h.step();
+    h.world.action_up(Action::Left);
+    h.world.mouse_up(MouseButton::Left, 0f32, 0f32);
+    h.step();
+    assert_ne!(h.world.get_text(), LINE);
+
+    h.run(1f32);
+    assert!(h.world.get_text().len() > typed);
+    assert_ne!(h.world.get_text(), LINE);
+    h.world.action_up(Action::Interact);
+    h.run(1f32);
+    assert_ne!(h.world.get_text(), LINE);
+
+    // and the press that held it finishes it when tapped
+    h.world.mouse_down(MouseButton::Left, 0f32, 0f32);
+    h.step();
+    h.world.mouse_up(MouseButton::Left, 0f32, 0f32);
+    assert_eq!(h.world.get_text(), LINE);
+}
@@ -0,0 +1,403 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::io::Read;
+
+use ggez::audio::{SoundData, Source, SpatialSource};
+use ggez::filesystem;
+use ggez::graphics::{Font, Image};
+use ggez::{Context, GameError, GameResult};
+
+use ld47_core::dialogue::Dialogue;
+use ld47_core::level::Level;
+use ld47_core::parse::ParseError;
+use ld47_core::strings::Strings;
+
+use crate::mods::Mod;
+use crate::resources::{ResourceDir, ResourceError};
+
+pub const MANIFEST: &str = "/assets.manifest";
+// the language used for anything the picked one doesn't have
+pub const DEFAULT_LANGUAGE: &str = "en";
+
+// the path of every asset by kind and name
+pub type AssetPaths = HashMap<(AssetKind, String), String>;
+
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub enum AssetKind {
+    Font,
+    Image,
+    Sound,
+    Level,
+    Dialogue,
+    Strings,
+}
+
+impl AssetKind {
+    pub fn get_name(self) -> &'static str {
+        match self {
+            AssetKind::Font => "font",
+            AssetKind::Image => "image",
+            AssetKind::Sound => "sound",
+            AssetKind::Level => "level",
+            AssetKind::Dialogue => "dialogue",
+            AssetKind::Strings => "strings",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<AssetKind> {
+        match name {
+            "font" => Some(AssetKind::Font),
+            "image" => Some(AssetKind::Image),
+            "sound" => Some(AssetKind::Sound),
+            "level" => Some(AssetKind::Level),
+            "dialogue" => Some(AssetKind::Dialogue),
+            "strings" => Some(AssetKind::Strings),
+            _ => None,
+        }
+    }
+}
+
+// Every asset the game uses, looked up by the name given to it in the
+// manifest. Files are only read the first time they are asked for and are kept
+// around after that, so starting a new run doesn't load anything again.
+pub struct Assets {
+    paths: AssetPaths,
+    fonts: RefCell<HashMap<String, Font>>,
+    images: RefCell<HashMap<String, Image>>,
+    sounds: RefCell<HashMap<String, SoundData>>,
+    levels: RefCell<HashMap<String, Level>>,
+    dialogues: RefCell<HashMap<String, Dialogue>>,
+    strings: RefCell<HashMap<String, Strings>>,
+}
+
+pub fn read_to_string(ctx: &mut Context, path: &str) -> GameResult<String> {
+    let mut file = filesystem::open(ctx, path)?;
+    let mut contents = String::new();
+    file.read_to_string(&mut contents)?;
+    Ok(contents)
+}
+
+// the lines that could not be read are returned as (where, why)
+pub fn parse_manifest(manifest: &str) -> (AssetPaths, Vec<(String, String)>) {
+    let mut paths = HashMap::new();
+    let mut failed = Vec::new();
+    for (idx, line) in manifest.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let words: Vec<&str> = line.split_whitespace().collect();
+        match (words.len(), AssetKind::from_name(words[0])) {
+            (3, Some(kind)) => {
+                paths.insert((kind, words[1].to_string()), words[2].to_string());
+            }
+            _ => failed.push((
+                format!("{} line {}", MANIFEST, idx + 1),
+                format!("invalid entry \"{}\"", line),
+            )),
+        }
+    }
+    (paths, failed)
+}
+
+impl Assets {
+    pub fn new(ctx: &mut Context, resource_dirs: &[ResourceDir], mods: &[Mod]) -> GameResult<Self> {
+        let manifest = match read_to_string(ctx, MANIFEST) {
+            Ok(manifest) => manifest,
+            Err(e) => {
+                let failed = vec![(String::from(MANIFEST), e.to_string())];
+                return Err(ResourceError::new(ctx, failed, 1, resource_dirs).into());
+            }
+        };
+
+        let (mut paths, mut failed) = parse_manifest(&manifest);
+        for m in mods.iter().filter(|m| m.enabled) {
+            apply_mod(ctx, &mut paths, m);
+        }
+
+        let assets = Self {
+            paths,
+            fonts: RefCell::new(HashMap::new()),
+            images: RefCell::new(HashMap::new()),
+            sounds: RefCell::new(HashMap::new()),
+            levels: RefCell::new(HashMap::new()),
+            dialogues: RefCell::new(HashMap::new()),
+            strings: RefCell::new(HashMap::new()),
+        };
+        // images and fonts are loaded now and kept, everything else is still
+        // read when first used and this only makes sure it can be
+        for ((kind, name), path) in &assets.paths {
+            let result = if !filesystem::exists(ctx, path) {
+                Err(String::from("not found"))
+            } else {
+                match kind {
+                    AssetKind::Font => Font::new(ctx, path)
+                        .map(|font| {
+                            assets.fonts.borrow_mut().insert(name.clone(), font);
+                        })
+                        .map_err(|e| e.to_string()),
+                    AssetKind::Image => Image::new(ctx, path)
+                        .map(|image| {
+                            assets.images.borrow_mut().insert(name.clone(), image);
+                        })
+                        .map_err(|e| e.to_string()),
+                    _ => check_file(ctx, *kind, path),
+                }
+            };
+            if let Err(reason) = result {
+                failed.push((path.clone(), reason));
+            }
+        }
+        if !failed.is_empty() {
+            failed.sort();
+            let count = assets.paths.len();
+            return Err(ResourceError::new(ctx, failed, count, resource_dirs).into());
+        }
+
+        Ok(assets)
+    }
+
+    // (kind, name, path) of everything in the manifest
+    pub fn get_files(&self) -> impl Iterator<Item = (AssetKind, &str, &str)> {
+        self.paths
+            .iter()
+            .map(|((kind, name), path)| (*kind, name.as_str(), path.as_str()))
+    }
+
+    // forgets a loaded asset so the next get reads it from disk again
+    pub fn invalidate(&self, kind: AssetKind, name: &str) {
+        match kind {
+            AssetKind::Font => {
+                self.fonts.borrow_mut().remove(name);
+            }
+            AssetKind::Image => {
+                self.images.borrow_mut().remove(name);
+            }
+            AssetKind::Sound => {
+                self.sounds.borrow_mut().remove(name);
+            }
+            AssetKind::Level => {
+                self.levels.borrow_mut().remove(name);
+            }
+            AssetKind::Dialogue => {
+                self.dialogues.borrow_mut().remove(name);
+            }
+            AssetKind::Strings => {
+                self.strings.borrow_mut().remove(name);
+            }
+        }
+    }
+
+    fn get_path(&self, kind: AssetKind, name: &str) -> GameResult<&str> {
+        match self.paths.get(&(kind, name.to_string())) {
+            Some(path) => Ok(path),
+            None => Err(GameError::ResourceLoadError(format!(
+                "No {} named \"{}\" in {}",
+                kind.get_name(),
+                name,
+                MANIFEST
+            ))),
+        }
+    }
+
+    pub fn get_font(&self, ctx: &mut Context, name: &str) -> GameResult<Font> {
+        if let Some(font) = self.fonts.borrow().get(name) {
+            return Ok(*font);
+        }
+        let path = self.get_path(AssetKind::Font, name)?;
+        let font = Font::new(ctx, path).map_err(|e| load_error(path, e))?;
+        self.fonts.borrow_mut().insert(name.to_string(), font);
+        Ok(font)
+    }
+
+    pub fn get_image(&self, ctx: &mut Context, name: &str) -> GameResult<Image> {
+        if let Some(image) = self.images.borrow().get(name) {
+            return Ok(image.clone());
+        }
+        let path = self.get_path(AssetKind::Image, name)?;
+        let image = Image::new(ctx, path).map_err(|e| load_error(path, e))?;
+        self.images
+            .borrow_mut()
+            .insert(name.to_string(), image.clone());
+        Ok(image)
+    }
+
+    pub fn get_sound(&self, ctx: &mut Context, name: &str) -> GameResult<SoundData> {
+        if let Some(data) = self.sounds.borrow().get(name) {
+            return Ok(data.clone());
+        }
+        let path = self.get_path(AssetKind::Sound, name)?;
+        let data = SoundData::new(ctx, path).map_err(|e| load_error(path, e))?;
+        // the data is only decoded when played, so check it now
+        if !data.can_play() {
+            return Err(GameError::ResourceLoadError(format!(
+                "\"{}\" is not a sound that can be played",
+                path
+            )));
+        }
+        self.sounds
+            .borrow_mut()
+            .insert(name.to_string(), data.clone());
+        Ok(data)
+    }
+
+    pub fn get_level(&self, ctx: &mut Context, name: &str) -> GameResult<Level> {
+        if let Some(level) = self.levels.borrow().get(name) {
+            return Ok(level.clone());
+        }
+        let path = self.get_path(AssetKind::Level, name)?;
+        let contents = read_to_string(ctx, path).map_err(|e| load_error(path, e))?;
+        let level = Level::parse(&contents).map_err(|errors| parse_error("level", path, errors))?;
+        self.levels
+            .borrow_mut()
+            .insert(name.to_string(), level.clone());
+        Ok(level)
+    }
+
+    pub fn get_dialogue(&self, ctx: &mut Context, name: &str) -> GameResult<Dialogue> {
+        if let Some(dialogue) = self.dialogues.borrow().get(name) {
+            return Ok(dialogue.clone());
+        }
+        let path = self.get_path(AssetKind::Dialogue, name)?;
+        let contents = read_to_string(ctx, path).map_err(|e| load_error(path, e))?;
+        let dialogue =
+            Dialogue::parse(&contents).map_err(|errors| parse_error("dialogue", path, errors))?;
+        self.dialogues
+            .borrow_mut()
+            .insert(name.to_string(), dialogue.clone());
+        Ok(dialogue)
+    }
+
+    fn get_language(&self, ctx: &mut Context, name: &str) -> GameResult<Strings> {
+        if let Some(strings) = self.strings.borrow().get(name) {
+            return Ok(strings.clone());
+        }
+        let path = self.get_path(AssetKind::Strings, name)?;
+        let contents = read_to_string(ctx, path).map_err(|e| load_error(path, e))?;
+        let strings =
+            Strings::parse(&contents).map_err(|errors| parse_error("strings", path, errors))?;
+        self.strings
+            .borrow_mut()
+            .insert(name.to_string(), strings.clone());
+        Ok(strings)
+    }
+
+    // The strings of the language with English for whatever it leaves out. A
+    // language that can't be loaded is reported and English is used instead,
+    // so a broken translation never stops the game.
+    pub fn get_strings(&self, ctx: &mut Context, language: &str) -> GameResult<Strings> {
+        let english = self.get_language(ctx, DEFAULT_LANGUAGE)?;
+        if language == DEFAULT_LANGUAGE {
+            return Ok(english);
+        }
+        match self.get_language(ctx, language) {
+            Ok(strings) => Ok(strings.with_fallback(&english)),
+            Err(e) => {
+                println!("WARNING: Using English: {}", e);
+                Ok(english)
+            }
+        }
+    }
+
+    // the names of every language in the manifest, sorted
+    pub fn get_languages(&self) -> Vec<&str> {
+        let mut languages: Vec<&str> = self
+            .get_files()
+            .filter(|(kind, _, _)| *kind == AssetKind::Strings)
+            .map(|(_, name, _)| name)
+            .collect();
+        languages.sort_unstable();
+        languages
+    }
+
+    pub fn get_source(&self, ctx: &mut Context, name: &str) -> GameResult<Source> {
+        let data = self.get_sound(ctx, name)?;
+        Source::from_data(ctx, data)
+    }
+
+    pub fn get_spatial_source(&self, ctx: &mut Context, name: &str) -> GameResult<SpatialSource> {
+        let data = self.get_sound(ctx, name)?;
+        SpatialSource::from_data(ctx, data)
+    }
+}
+
+// Text files are parsed, sounds are decoded and fonts and images are loaded, so
+// a broken one is reported before the game starts instead of in the middle of
+// it
+fn check_file(ctx: &mut Context, kind: AssetKind, path: &str) -> Result<(), String> {
+    let read = |ctx: &mut Context| read_to_string(ctx, path).map_err(|e| e.to_string());
+    let errors = match kind {
+        AssetKind::Font => return Font::new(ctx, path).map(|_| ()).map_err(|e| e.to_string()),
+        AssetKind::Image => return Image::new(ctx, path).map(|_| ()).map_err(|e| e.to_string()),
+        AssetKind::Sound => {
+            let data = SoundData::new(ctx, path).map_err(|e| e.to_string())?;
+            return if data.can_play() {
+                Ok(())
+            } else {
+                Err(String::from("not a sound that can be played"))
+            };
+        }
+        AssetKind::Level => Level::parse(&read(ctx)?).err(),
+        AssetKind::Dialogue => Dialogue::parse(&read(ctx)?).err(),
+        AssetKind::Strings => Strings::parse(&read(ctx)?).err(),
+    };
+    match errors {
+        Some(errors) => Err(errors
+            .iter()
+            .map(|error| error.to_string())
+            .collect::<Vec<String>>()
+            .join(", ")),
+        None => Ok(()),
+    }
+}
+
+// A mod is only used if everything in it checks out, otherwise the game goes
+// on without it
+fn apply_mod(ctx: &mut Context, paths: &mut AssetPaths, m: &Mod) {
+    let mut problems = Vec::new();
+    for entry in &m.entries {
+        let exists = paths.contains_key(&(entry.kind, entry.name.clone()));
+        if entry.replaces && !exists {
+            problems.push(format!(
+                "replaces {} \"{}\" which is not in the game",
+                entry.kind.get_name(),
+                entry.name
+            ));
+        } else if !entry.replaces && exists {
+            problems.push(format!(
+                "adds {} \"{}\" which is already in the game",
+                entry.kind.get_name(),
+                entry.name
+            ));
+        }
+        if !filesystem::exists(ctx, &entry.path) {
+            problems.push(format!("\"{}\" not found", entry.path));
+        } else if let Err(reason) = check_file(ctx, entry.kind, &entry.path) {
+            problems.push(format!("\"{}\" can't be used: {}", entry.path, reason));
+        }
+    }
+    if !problems.is_empty() {
+        println!("WARNING: Not using mod \"{}\" {}:", m.name, m.version);
+        for problem in problems {
+            println!("    {}", problem);
+        }
+        return;
+    }
+
+    for entry in &m.entries {
+        paths.insert((entry.kind, entry.name.clone()), entry.path.clone());
+    }
+    println!("Using mod \"{}\" {}", m.name, m.version);
+}
+
+fn load_error(path: &str, error: GameError) -> GameError {
+    GameError::ResourceLoadError(format!("Failed to load \"{}\": {}", path, error))
+}
+
+fn parse_error(kind: &str, path: &str, errors: Vec<ParseError>) -> GameError {
+    let mut message = format!("Invalid {} \"{}\":", kind, path);
+    for error in errors {
+        message.push_str(&format!("\n    {}", error));
+    }
+    GameError::ResourceLoadError(message)
+}
@@ -0,0 +1,439 @@
+use std::collections::HashMap;
+
+use ggez::audio::{SoundSource, Source, SpatialSource};
+use ggez::{Context, GameResult};
+
+use ld47_core::level::AmbientLayout;
+use ld47_core::world::{Music, Sfx};
+
+use crate::assets::Assets;
+
+const TEXT_SFX_PITCH: f32 = 1.4f32;
+const CROSSFADE_TIME: f32 = 1.5f32;
+// how long an ambient sound takes to go from silent to full volume
+const AMBIENT_FADE_TIME: f32 = 2f32;
+// music is turned down to this while text is typing
+const DUCK_VOLUME: f32 = 0.4f32;
+const DUCK_FADE_TIME: f32 = 0.25f32;
+// how long after the last letter the music stays down
+const DUCK_HOLD_TIME: f32 = 0.5f32;
+// rodio also turns a sound down the further it is from an ear, so sounds are
+// kept between the ears where that does nothing and are turned down here instead
+const EAR_OFFSET: f32 = 0.5f32;
+
+// (track, sound name, loops)
+const MUSIC: [(Music, &str, bool); 4] = [
+    (Music::Main, "main_music", true),
+    (Music::Discovery, "discovery_music", false),
+    (Music::BadNews, "bad_news_music", true),
+    (Music::Ending, "ending_music", false),
+];
+// (effect, sound name, how far from the player in pixels it can be heard)
+const SFX: [(Sfx, &str, f32); 4] = [
+    (Sfx::Text, "text", 800f32),
+    (Sfx::Door, "door", 1200f32),
+    (Sfx::Error, "error", 800f32),
+    (Sfx::Success, "success", 800f32),
+];
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+enum FadeOut {
+    Stop,
+    // keeps the position to carry on from later
+    Pause,
+}
+
+struct Track {
+    source: Source,
+    gain: f32,
+    fade_out: Option<FadeOut>,
+}
+
+impl Track {
+    fn is_active(&self) -> bool {
+        !self.source.stopped() && !self.source.paused()
+    }
+}
+
+struct Effect {
+    source: SpatialSource,
+    range: f32,
+    // where in the room it was played, or at the player
+    x: Option<f32>,
+}
+
+impl Effect {
+    // panned to the side it is on and quieter the further away it is
+    fn place(&mut self, listener_x: f32, volume: f32) {
+        let offset = self.x.map_or(0f32, |x| x - listener_x);
+        let falloff = (1f32 - offset.abs() / self.range).max(0f32);
+        let pan = (offset / self.range).clamp(-1f32, 1f32) * EAR_OFFSET;
+        self.source.set_position([pan, 0f32, 0f32]);
+        self.source.set_volume(volume * falloff);
+    }
+}
+
+fn new_effect_source(ctx: &mut Context, assets: &Assets, name: &str) -> GameResult<SpatialSource> {
+    let mut source = assets.get_spatial_source(ctx, name)?;
+    source.set_ears([-EAR_OFFSET, 0f32, 0f32], [EAR_OFFSET, 0f32, 0f32]);
+    if name == "text" {
+        source.set_pitch(TEXT_SFX_PITCH);
+    }
+    Ok(source)
+}
+
+struct AmbientLayer {
+    source: Source,
+    gain: f32,
+    target: f32,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub enum Channel {
+    Music,
+    Sfx,
+}
+
+// Owns every sound the game plays, split into a music and a sound effects
+// channel that each have their own volume. Only one music track plays at a
+// time, starting one fades out whatever was playing before while it fades in.
+pub struct Audio {
+    music: HashMap<Music, Track>,
+    sfx: HashMap<Sfx, Effect>,
+    // where the player is, sounds are heard from there
+    listener_x: f32,
+    // looping sounds by name, played along with the sound effects
+    ambient: HashMap<String, AmbientLayer>,
+    // (sound name, how far right of the player) of the sounds started since
+    // they were last taken
+    started: Vec<(String, f32)>,
+    current_music: Option<Music>,
+    // the track to carry on with once a stinger is over
+    resume_music: Option<Music>,
+    volumes: HashMap<Channel, f32>,
+    muted: bool,
+    duck: f32,
+    duck_timer: f32,
+}
+
+impl Audio {
+    pub fn new(ctx: &mut Context, assets: &Assets) -> GameResult<Self> {
+        let mut music = HashMap::new();
+        for (track, name, repeat) in MUSIC.iter() {
+            let mut source = assets.get_source(ctx, name)?;
+            source.set_repeat(*repeat);
+            music.insert(
+                *track,
+                Track {
+                    source,
+                    gain: 0f32,
+                    fade_out: None,
+                },
+            );
+        }
+        let mut sfx = HashMap::new();
+        for (effect, name, range) in SFX.iter() {
+            sfx.insert(
+                *effect,
+                Effect {
+                    source: new_effect_source(ctx, assets, name)?,
+                    range: *range,
+                    x: None,
+                },
+            );
+        }
+        let mut volumes = HashMap::new();
+        volumes.insert(Channel::Music, 1f32);
+        volumes.insert(Channel::Sfx, 1f32);
+        Ok(Self {
+            music,
+            sfx,
+            listener_x: 0f32,
+            ambient: HashMap::new(),
+            started: Vec::new(),
+            current_music: None,
+            resume_music: None,
+            volumes,
+            muted: false,
+            duck: 1f32,
+            duck_timer: 0f32,
+        })
+    }
+
+    fn get_volume(&self, channel: Channel) -> f32 {
+        if self.muted {
+            0f32
+        } else {
+            self.volumes[&channel]
+        }
+    }
+
+    fn apply_volumes(&mut self) {
+        let music_volume = self.get_volume(Channel::Music) * self.duck;
+        for track in self.music.values_mut() {
+            track.source.set_volume(music_volume * track.gain);
+        }
+        let sfx_volume = self.get_volume(Channel::Sfx);
+        for effect in self.sfx.values_mut() {
+            effect.place(self.listener_x, sfx_volume);
+        }
+        for layer in self.ambient.values_mut() {
+            layer.source.set_volume(sfx_volume * layer.gain);
+        }
+    }
+
+    pub fn set_volume(&mut self, channel: Channel, volume: f32) {
+        self.volumes.insert(channel, volume);
+        self.apply_volumes();
+    }
+
+    pub fn set_muted(&mut self, muted: bool) {
+        self.muted = muted;
+        self.apply_volumes();
+    }
+
+    pub fn set_listener_x(&mut self, x: f32) {
+        self.listener_x = x;
+    }
+
+    pub fn play_sfx(&mut self, sfx: Sfx) -> GameResult<()> {
+        self.play_effect(sfx, None)
+    }
+
+    // x is where in the room the sound comes from
+    pub fn play_sfx_at(&mut self, sfx: Sfx, x: f32) -> GameResult<()> {
+        self.play_effect(sfx, Some(x))
+    }
+
+    fn play_effect(&mut self, sfx: Sfx, x: Option<f32>) -> GameResult<()> {
+        let volume = self.get_volume(Channel::Sfx);
+        match self.sfx.get_mut(&sfx) {
+            Some(effect) => {
+                effect.x = x;
+                effect.place(self.listener_x, volume);
+                effect.source.play()?;
+            }
+            None => return Ok(()),
+        }
+        if let Some((_, name, _)) = SFX.iter().find(|(effect, _, _)| *effect == sfx) {
+            let offset = x.map_or(0f32, |x| x - self.listener_x);
+            self.started.push((name.to_string(), offset));
+        }
+        Ok(())
+    }
+
+    fn add_started_music(&mut self, music: Music) {
+        if let Some((_, name, _)) = MUSIC.iter().find(|(track, _, _)| *track == music) {
+            self.started.push((name.to_string(), 0f32));
+        }
+    }
+
+    pub fn take_started(&mut self) -> Vec<(String, f32)> {
+        std::mem::take(&mut self.started)
+    }
+
+    // keeps the music turned down for a moment, called for every letter typed
+    pub fn duck(&mut self) {
+        self.duck_timer = DUCK_HOLD_TIME;
+    }
+
+    pub fn play_music(&mut self, music: Music) -> GameResult<()> {
+        for (other, track) in self.music.iter_mut() {
+            if *other != music && track.is_active() {
+                track.fade_out = Some(FadeOut::Stop);
+            }
+        }
+        self.current_music = Some(music);
+        self.resume_music = None;
+        self.add_started_music(music);
+        self.fade_in(music)
+    }
+
+    fn fade_in(&mut self, music: Music) -> GameResult<()> {
+        let track = match self.music.get_mut(&music) {
+            Some(track) => track,
+            None => return Ok(()),
+        };
+        track.fade_out = None;
+        if track.source.paused() {
+            track.source.resume();
+        } else if track.source.stopped() {
+            track.gain = 0f32;
+            track.source.play()?;
+        }
+        Ok(())
+    }
+
+    pub fn stop_music(&mut self, music: Music) {
+        if let Some(track) = self.music.get_mut(&music) {
+            track.fade_out = Some(FadeOut::Stop);
+        }
+        if self.current_music == Some(music) {
+            self.current_music = None;
+        }
+        if self.resume_music == Some(music) {
+            self.resume_music = None;
+        }
+    }
+
+    // plays a track once, the one it interrupts is faded out and then carries on
+    // from where it was
+    pub fn play_stinger(&mut self, music: Music) -> GameResult<()> {
+        if let Some(current) = self.current_music.filter(|current| *current != music) {
+            if let Some(track) = self.music.get_mut(&current) {
+                if track.is_active() {
+                    track.fade_out = Some(FadeOut::Pause);
+                    self.resume_music = Some(current);
+                }
+            }
+        }
+        self.current_music = Some(music);
+        self.add_started_music(music);
+        self.fade_in(music)?;
+        // a stinger comes in right away
+        if let Some(track) = self.music.get_mut(&music) {
+            track.gain = 1f32;
+        }
+        Ok(())
+    }
+
+    // loads the ambient sounds that are not loaded yet
+    pub fn load_ambient(
+        &mut self,
+        ctx: &mut Context,
+        assets: &Assets,
+        sounds: &[&str],
+    ) -> GameResult<()> {
+        for sound in sounds {
+            if !self.ambient.contains_key(*sound) {
+                let mut source = assets.get_source(ctx, sound)?;
+                source.set_repeat(true);
+                self.ambient.insert(
+                    sound.to_string(),
+                    AmbientLayer {
+                        source,
+                        gain: 0f32,
+                        target: 0f32,
+                    },
+                );
+            }
+        }
+        Ok(())
+    }
+
+    // the given layers fade in to their volume and every other one fades out
+    pub fn set_ambient(&mut self, layers: &[AmbientLayout]) -> GameResult<()> {
+        for (name, layer) in self.ambient.iter_mut() {
+            layer.target = layers
+                .iter()
+                .find(|ambient| ambient.sound == *name)
+                .map_or(0f32, |ambient| ambient.volume);
+            if layer.target > 0f32 && layer.source.stopped() {
+                layer.gain = 0f32;
+                layer.source.play()?;
+                self.started.push((name.clone(), 0f32));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn update(&mut self, dt: f32) -> GameResult<()> {
+        self.duck_timer = (self.duck_timer - dt).max(0f32);
+        let duck_target = if self.duck_timer > 0f32 {
+            DUCK_VOLUME
+        } else {
+            1f32
+        };
+        let duck_step = (1f32 - DUCK_VOLUME) * dt / DUCK_FADE_TIME;
+        if self.duck < duck_target {
+            self.duck = (self.duck + duck_step).min(duck_target);
+        } else {
+            self.duck = (self.duck - duck_step).max(duck_target);
+        }
+
+        let fade_step = dt / CROSSFADE_TIME;
+        for track in self.music.values_mut() {
+            match track.fade_out {
+                Some(fade_out) => {
+                    track.gain -= fade_step;
+                    if track.gain <= 0f32 {
+                        track.gain = 0f32;
+                        track.fade_out = None;
+                        match fade_out {
+                            FadeOut::Stop => track.source.stop(),
+                            FadeOut::Pause => track.source.pause(),
+                        }
+                    }
+                }
+                None => {
+                    if track.is_active() {
+                        track.gain = (track.gain + fade_step).min(1f32);
+                    }
+                }
+            }
+        }
+
+        let ambient_step = dt / AMBIENT_FADE_TIME;
+        for layer in self.ambient.values_mut() {
+            if layer.gain < layer.target {
+                layer.gain = (layer.gain + ambient_step).min(layer.target);
+            } else if layer.gain > layer.target {
+                layer.gain = (layer.gain - ambient_step).max(layer.target);
+                if layer.gain <= 0f32 {
+                    layer.source.stop();
+                }
+            }
+        }
+
+        if let (Some(current), Some(resume)) = (self.current_music, self.resume_music) {
+            if self.music[&current].source.stopped() {
+                self.current_music = Some(resume);
+                self.resume_music = None;
+                self.fade_in(resume)?;
+            }
+        }
+        self.apply_volumes();
+        Ok(())
+    }
+
+    // a sound that is playing keeps going with the new data, from the start
+    pub fn reload(&mut self, ctx: &mut Context, assets: &Assets, name: &str) -> GameResult<()> {
+        if let Some((track, _, _)) = MUSIC.iter().find(|(_, n, _)| *n == name) {
+            let source = match self.music.get_mut(track) {
+                Some(track) => &mut track.source,
+                None => return Ok(()),
+            };
+            let mut new_source = assets.get_source(ctx, name)?;
+            new_source.set_repeat(source.repeat());
+            new_source.set_volume(source.volume());
+            if source.playing() {
+                source.stop();
+                new_source.play()?;
+            }
+            *source = new_source;
+        } else if let Some((effect, _, _)) = SFX.iter().find(|(_, n, _)| *n == name) {
+            let source = match self.sfx.get_mut(effect) {
+                Some(effect) => &mut effect.source,
+                None => return Ok(()),
+            };
+            let mut new_source = new_effect_source(ctx, assets, name)?;
+            new_source.set_volume(source.volume());
+            if source.playing() {
+                source.stop();
+                new_source.play()?;
+            }
+            *source = new_source;
+            self.apply_volumes();
+        } else if let Some(layer) = self.ambient.get_mut(name) {
+            let mut new_source = assets.get_source(ctx, name)?;
+            new_source.set_repeat(true);
+            new_source.set_volume(layer.source.volume());
+            if layer.source.playing() {
+                layer.source.stop();
+                new_source.play()?;
+            }
+            layer.source = new_source;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,95 @@
+use ggez::graphics::{self, Color, DrawMode, DrawParam, Mesh, Rect};
+use ggez::{Context, GameResult};
+
+use ld47_core::strings::Strings;
+use ld47_core::world::{GROUND_HEIGHT, SCREEN_HEIGHT};
+
+use crate::fonts::FontStack;
+
+const CAPTION_TIME: f32 = 3f32;
+const MAX_CAPTIONS: usize = 3;
+const CAPTION_HEIGHT: f32 = 30f32;
+// sounds further than this from the player show which side they are on
+const SIDE_DISTANCE: f32 = 100f32;
+
+// Short text shown at the bottom of the screen for every sound that starts,
+// for playing muted or without hearing it. The caption of a sound is the
+// string caption.<sound name>, sounds without one are not captioned.
+pub struct Captions {
+    fonts: FontStack,
+    strings: Strings,
+    // (caption, time left)
+    shown: Vec<(String, f32)>,
+}
+
+impl Captions {
+    pub fn new(fonts: FontStack, strings: Strings) -> Self {
+        Self {
+            fonts,
+            strings,
+            shown: Vec::new(),
+        }
+    }
+
+    pub fn set_language(&mut self, fonts: FontStack, strings: Strings) {
+        self.fonts = fonts;
+        self.strings = strings;
+    }
+
+    // offset is how far right of the player the sound is
+    pub fn add(&mut self, sound: &str, offset: f32) {
+        let caption = match self.strings.find(&format!("caption.{}", sound)) {
+            Some(caption) => caption,
+            None => return,
+        };
+        let caption = if offset < -SIDE_DISTANCE {
+            format!("< {}", caption)
+        } else if offset > SIDE_DISTANCE {
+            format!("{} >", caption)
+        } else {
+            caption.to_string()
+        };
+        // the same sound again only keeps its caption up longer
+        self.shown.retain(|(shown, _)| *shown != caption);
+        self.shown.push((caption, CAPTION_TIME));
+        if self.shown.len() > MAX_CAPTIONS {
+            self.shown.remove(0);
+        }
+    }
+
+    pub fn update(&mut self, dt: f32) {
+        for (_, timer) in self.shown.iter_mut() {
+            *timer -= dt;
+        }
+        self.shown.retain(|(_, timer)| *timer > 0f32);
+    }
+
+    pub fn draw(&self, ctx: &mut Context) -> GameResult<()> {
+        let bottom = SCREEN_HEIGHT - GROUND_HEIGHT - 10f32;
+        for (idx, (caption, _)) in self.shown.iter().rev().enumerate() {
+            let text = self.fonts.text(caption, 22f32);
+            let width = text.width(ctx) as f32;
+            let y = bottom - (idx + 1) as f32 * CAPTION_HEIGHT;
+            let background = Mesh::new_rectangle(
+                ctx,
+                DrawMode::fill(),
+                Rect::new(
+                    400f32 - width / 2f32 - 8f32,
+                    y,
+                    width + 16f32,
+                    CAPTION_HEIGHT - 4f32,
+                ),
+                Color::new(0f32, 0f32, 0f32, 0.7f32),
+            )?;
+            graphics::draw(ctx, &background, DrawParam::new())?;
+            graphics::draw(
+                ctx,
+                &text,
+                DrawParam::new()
+                    .dest([400f32 - width / 2f32, y + 1f32])
+                    .color(graphics::WHITE),
+            )?;
+        }
+        Ok(())
+    }
+}
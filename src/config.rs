@@ -0,0 +1,109 @@
+use std::path::PathBuf;
+
+use ld47_core::world::{Difficulty, Options, Room};
+
+pub const USAGE: &str = "\
+Usage: ld47_stuckinaloop [options]
+
+Options:
+    --windowed                  Run in a window
+    --fullscreen                Run fullscreen
+    --resolution <W>x<H>        Size of the window, the game is scaled to fit
+                                (these three override the saved settings)
+    --start-room <room>         Start in a room instead of the stasis pod, one of
+                                StasisPod, LeftOfPod, MainHallFrontOfPod,
+                                WindowRightHall, LeftHall, FarRightHall,
+                                Computer or Final
+    --skip-intro                Start right after getting out of the pod
+    --difficulty <difficulty>   easy, normal or hard
+    --seed <number>             Scramble the puzzles with this seed
+    --resources <path>          Where to look for the resources directory
+    --mute                      Play no sound, whatever the volume settings
+    --replay <file>             Play back a recorded session
+    --record <file>             Record this session
+    --dev                       Reload changed levels, images and sounds
+    --headless                  Check the resources without opening a window
+    --help                      Show this text";
+
+// Everything that can be set on the command line, before the window is created
+#[derive(Default)]
+pub struct Config {
+    // the window settings are only overridden when given
+    pub fullscreen: Option<bool>,
+    pub resolution: Option<(f32, f32)>,
+    pub resources: Option<PathBuf>,
+    pub mute: bool,
+    pub replay: Option<String>,
+    pub record: Option<String>,
+    pub dev_mode: bool,
+    pub headless: bool,
+    pub help: bool,
+    // the text speed is taken from the settings, or the replay being played
+    pub world: Options,
+}
+
+fn get_value<I: Iterator<Item = String>>(args: &mut I, arg: &str) -> Result<String, String> {
+    args.next()
+        .ok_or_else(|| format!("\"{}\" needs a value", arg))
+}
+
+pub fn parse_resolution(value: &str) -> Option<(f32, f32)> {
+    let mut parts = value.split('x');
+    let width: u32 = parts.next()?.parse().ok()?;
+    let height: u32 = parts.next()?.parse().ok()?;
+    if parts.next().is_some() || width == 0 || height == 0 {
+        return None;
+    }
+    Some((width as f32, height as f32))
+}
+
+impl Config {
+    pub fn from_args<I: Iterator<Item = String>>(mut args: I) -> Result<Config, String> {
+        let mut config = Config::default();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--windowed" => config.fullscreen = Some(false),
+                "--fullscreen" => config.fullscreen = Some(true),
+                "--resolution" => {
+                    let value = get_value(&mut args, &arg)?;
+                    config.resolution = Some(
+                        parse_resolution(&value)
+                            .ok_or_else(|| format!("Invalid resolution \"{}\"", value))?,
+                    );
+                }
+                "--start-room" => {
+                    let value = get_value(&mut args, &arg)?;
+                    config.world.start_room = Some(
+                        Room::from_name(&value)
+                            .ok_or_else(|| format!("Unknown room \"{}\"", value))?,
+                    );
+                }
+                "--skip-intro" => config.world.skip_intro = true,
+                "--difficulty" => {
+                    let value = get_value(&mut args, &arg)?;
+                    config.world.difficulty = Difficulty::from_name(&value)
+                        .ok_or_else(|| format!("Unknown difficulty \"{}\"", value))?;
+                }
+                "--seed" => {
+                    let value = get_value(&mut args, &arg)?;
+                    config.world.seed = Some(
+                        value
+                            .parse()
+                            .map_err(|_| format!("Invalid seed \"{}\"", value))?,
+                    );
+                }
+                "--resources" => {
+                    config.resources = Some(PathBuf::from(get_value(&mut args, &arg)?))
+                }
+                "--mute" => config.mute = true,
+                "--replay" => config.replay = Some(get_value(&mut args, &arg)?),
+                "--record" => config.record = Some(get_value(&mut args, &arg)?),
+                "--dev" => config.dev_mode = true,
+                "--headless" => config.headless = true,
+                "--help" | "-h" => config.help = true,
+                _ => return Err(format!("Unknown argument \"{}\"", arg)),
+            }
+        }
+        Ok(config)
+    }
+}
@@ -0,0 +1,155 @@
+use std::collections::HashMap;
+
+use ggez::graphics::{self, Align, Color, DrawMode, DrawParam, Image, Mesh, Rect};
+use ggez::{Context, GameResult};
+
+use ld47_core::dialogue::{self, Dialogue};
+use ld47_core::world::World;
+
+use crate::assets::Assets;
+use crate::fonts::FontStack;
+
+const BOX_RECT: [f32; 4] = [20f32, 20f32, 760f32, 150f32];
+const PORTRAIT_SIZE: f32 = 128f32;
+const PADDING: f32 = 11f32;
+
+// The box at the top of the screen that shows who is talking and what they
+// say, with their portrait beside it if they have one, and the choices to
+// pick from below it
+pub struct DialogueView {
+    fonts: FontStack,
+    portraits: HashMap<String, Image>,
+}
+
+impl DialogueView {
+    pub fn new(fonts: FontStack) -> Self {
+        Self {
+            fonts,
+            portraits: HashMap::new(),
+        }
+    }
+
+    pub fn set_fonts(&mut self, fonts: FontStack) {
+        self.fonts = fonts;
+    }
+
+    pub fn load_portraits(
+        &mut self,
+        ctx: &mut Context,
+        assets: &Assets,
+        dialogue: &Dialogue,
+    ) -> GameResult<()> {
+        self.portraits.clear();
+        for name in dialogue.get_portraits() {
+            self.portraits
+                .insert(name.to_string(), assets.get_image(ctx, name)?);
+        }
+        Ok(())
+    }
+
+    pub fn reload_image(
+        &mut self,
+        ctx: &mut Context,
+        assets: &Assets,
+        name: &str,
+    ) -> GameResult<()> {
+        if let Some(image) = self.portraits.get_mut(name) {
+            *image = assets.get_image(ctx, name)?;
+        }
+        Ok(())
+    }
+
+    pub fn draw(&self, ctx: &mut Context, world: &World) -> GameResult<()> {
+        let (speaker, line) = match world.get_dialogue() {
+            Some(dialogue) => dialogue,
+            None => return Ok(()),
+        };
+        let background = Mesh::new_rectangle(
+            ctx,
+            DrawMode::fill(),
+            Rect::new(BOX_RECT[0], BOX_RECT[1], BOX_RECT[2], BOX_RECT[3]),
+            Color::new(0f32, 0f32, 0f32, 0.8f32),
+        )?;
+        graphics::draw(ctx, &background, DrawParam::new())?;
+
+        let mut text_x = BOX_RECT[0] + PADDING;
+        let portrait = world
+            .get_portrait()
+            .and_then(|name| self.portraits.get(name));
+        if let Some(image) = portrait {
+            let (x, y) = (BOX_RECT[0] + PADDING, BOX_RECT[1] + PADDING);
+            let scale = PORTRAIT_SIZE / image.width().max(image.height()) as f32;
+            graphics::draw(
+                ctx,
+                image,
+                DrawParam::new().dest([x, y]).scale([scale, scale]),
+            )?;
+            let frame = Mesh::new_rectangle(
+                ctx,
+                DrawMode::stroke(2f32),
+                Rect::new(x, y, PORTRAIT_SIZE, PORTRAIT_SIZE),
+                graphics::WHITE,
+            )?;
+            graphics::draw(ctx, &frame, DrawParam::new())?;
+            text_x += PORTRAIT_SIZE + PADDING;
+        }
+
+        let speaker_text = self.fonts.text(speaker, 24f32);
+        graphics::draw(
+            ctx,
+            &speaker_text,
+            DrawParam::new()
+                .dest([text_x, BOX_RECT[1] + PADDING])
+                .color(Color::from_rgb(0xff, 0xe0, 0x80)),
+        )?;
+        let line_text = self.fonts.wrapped(
+            line,
+            22f32,
+            BOX_RECT[0] + BOX_RECT[2] - PADDING - text_x,
+            Align::Left,
+        );
+        graphics::draw(
+            ctx,
+            &line_text,
+            DrawParam::new()
+                .dest([text_x, BOX_RECT[1] + PADDING + 34f32])
+                .color(graphics::WHITE),
+        )?;
+
+        if let Some((choices, picked)) = world.get_choices() {
+            for (idx, choice) in choices.iter().enumerate() {
+                let rect = dialogue::get_choice_rect(idx);
+                let rect = Rect::new(rect[0], rect[1], rect[2], rect[3]);
+                let background = Mesh::new_rectangle(
+                    ctx,
+                    DrawMode::fill(),
+                    rect,
+                    Color::new(0f32, 0f32, 0f32, 0.8f32),
+                )?;
+                graphics::draw(ctx, &background, DrawParam::new())?;
+                let text = self.fonts.wrapped(
+                    world.get_strings().resolve(&choice.text),
+                    20f32,
+                    rect.w - PADDING * 2f32,
+                    Align::Left,
+                );
+                let color = if idx == picked {
+                    let frame =
+                        Mesh::new_rectangle(ctx, DrawMode::stroke(2f32), rect, graphics::WHITE)?;
+                    graphics::draw(ctx, &frame, DrawParam::new())?;
+                    Color::from_rgb(0xff, 0xe0, 0x80)
+                } else {
+                    graphics::WHITE
+                };
+                graphics::draw(
+                    ctx,
+                    &text,
+                    DrawParam::new()
+                        .dest([rect.x + PADDING, rect.y + 8f32])
+                        .color(color),
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,58 @@
+use ggez::graphics::{Align, Font, Scale, Text, TextFragment};
+use ggez::{Context, GameResult};
+
+use ld47_core::script::{self, Script};
+use ld47_core::strings::Strings;
+
+use crate::assets::Assets;
+
+// The fonts a language is drawn with, picked by its strings. Text is split by
+// script and each part is drawn with the font given for that script, or the
+// default one, so a line mixing scripts still shows every character.
+#[derive(Clone)]
+pub struct FontStack {
+    default: Font,
+    // the font picked for each script
+    fonts: Vec<(Script, Font)>,
+}
+
+impl FontStack {
+    pub fn new(ctx: &mut Context, assets: &Assets, strings: &Strings) -> GameResult<Self> {
+        let default = assets.get_font(ctx, strings.get("font.default"))?;
+        let mut fonts = Vec::new();
+        for script in Script::ALL.iter() {
+            if let Some(name) = script::get_font_name(strings, *script) {
+                fonts.push((*script, assets.get_font(ctx, name)?));
+            }
+        }
+        Ok(Self { default, fonts })
+    }
+
+    pub fn get_font(&self, script: Script) -> Font {
+        self.fonts
+            .iter()
+            .find(|(with, _)| *with == script)
+            .map(|(_, font)| *font)
+            .unwrap_or(self.default)
+    }
+
+    pub fn text(&self, text: &str, size: f32) -> Text {
+        let mut result = Text::default();
+        for (script, run) in script::split(text) {
+            result.add(
+                TextFragment::new(run)
+                    .font(self.get_font(script))
+                    .scale(Scale::uniform(size)),
+            );
+        }
+        result
+    }
+
+    // broken into lines that fit in the width, at spaces or between
+    // characters for scripts written without them
+    pub fn wrapped(&self, text: &str, size: f32, width: f32, align: Align) -> Text {
+        let mut result = self.text(text, size);
+        result.set_bounds([width, f32::INFINITY], align);
+        result
+    }
+}
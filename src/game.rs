@@ -1,25 +1,59 @@
 use std::cell::RefCell;
+use std::collections::HashMap;
 use std::rc::Rc;
 
-use ggez::event::EventHandler;
-use ggez::graphics::{self, Font};
+use ggez::conf::FullscreenType;
+use ggez::event::{Axis, Button, EventHandler};
+use ggez::graphics::{self, Rect};
+use ggez::input::gamepad::GamepadId;
 use ggez::input::keyboard::{KeyCode, KeyMods};
 use ggez::input::mouse::MouseButton;
+use ggez::timer;
 use ggez::{Context, GameResult};
 
+use ld47_core::world::{SCREEN_HEIGHT, SCREEN_WIDTH};
+
+use crate::assets::{AssetKind, Assets};
+use crate::config::Config;
+use crate::hotreload::HotReload;
+use crate::input::{Action, InputMap};
+use crate::mods::{self, Mod};
 use crate::player::Player;
+use crate::replay::{Recorder, Replay, ReplayEvent};
+use crate::resources::ResourceDir;
 use crate::scenes::Scene;
-use crate::scenes::{gamestart::GameStartScene, mainscene::MainScene};
+use crate::scenes::{
+    gamestart::GameStartScene, mainscene::MainScene, mods::ModsScene, settings::SettingsScene,
+};
+use crate::settings::Settings;
+
+const UPDATE_RATE: u32 = 60;
+const FIXED_DT: f32 = 1f32 / UPDATE_RATE as f32;
+const MAX_STEPS_PER_FRAME: u32 = 5;
 
 pub struct Game {
     current_scene: Box<dyn Scene>,
     state: GameState,
     player: Rc<RefCell<Player>>,
-    font: Font,
+    assets: Assets,
+    resource_dirs: Vec<ResourceDir>,
+    mods: Rc<RefCell<Vec<Mod>>>,
+    settings: Rc<RefCell<Settings>>,
+    input_map: InputMap,
+    held_axes: HashMap<(GamepadId, Axis), Action>,
+    replay: Option<Replay>,
+    recorder: Option<Recorder>,
+    hot_reload: Option<HotReload>,
+    config: Config,
+    // mouse positions are scaled from this to the screen size
+    window_size: (f32, f32),
 }
 
+#[derive(Copy, Clone, PartialEq)]
 pub enum GameState {
     GameStart,
+    Mods,
+    Settings,
     MainState,
 }
 
@@ -27,44 +61,278 @@ impl GameState {
     fn get_scene(
         &self,
         ctx: &mut Context,
-        font: Font,
+        assets: &Assets,
         player: Rc<RefCell<Player>>,
-    ) -> Box<dyn Scene> {
+        mods: Rc<RefCell<Vec<Mod>>>,
+        settings: Rc<RefCell<Settings>>,
+        config: &Config,
+    ) -> GameResult<Box<dyn Scene>> {
         match self {
-            GameState::GameStart => GameStartScene::new_boxed(ctx, font, player),
-            GameState::MainState => MainScene::new_boxed(ctx, font, player),
+            GameState::GameStart => Ok(GameStartScene::new_boxed(
+                ctx,
+                assets,
+                player,
+                &settings.borrow(),
+            )?),
+            GameState::Mods => Ok(ModsScene::new_boxed(ctx, assets, mods, &settings.borrow())?),
+            GameState::Settings => Ok(SettingsScene::new_boxed(ctx, assets, settings)?),
+            GameState::MainState => Ok(MainScene::new_boxed(
+                ctx,
+                assets,
+                player,
+                &settings.borrow(),
+                config,
+            )?),
         }
     }
 
     fn get_next_state(&self) -> GameState {
         match self {
             GameState::GameStart => GameState::MainState,
+            GameState::Mods => GameState::GameStart,
+            GameState::Settings => GameState::GameStart,
             GameState::MainState => GameState::GameStart,
         }
     }
 }
 
 impl Game {
-    pub fn new(ctx: &mut Context) -> Game {
-        let font = Font::new(ctx, "/ClearSans-Regular.ttf").unwrap();
-        let player = Rc::new(RefCell::new(Player::new(ctx, graphics::WHITE).unwrap()));
-        let game = Game {
-            current_scene: GameStartScene::new_boxed(ctx, font, player.clone()),
+    pub fn new(
+        ctx: &mut Context,
+        config: Config,
+        resource_dirs: Vec<ResourceDir>,
+        replay: Option<Replay>,
+        recorder: Option<Recorder>,
+    ) -> GameResult<Game> {
+        // drawing is always done as if the window is the original size
+        graphics::set_screen_coordinates(ctx, Rect::new(0f32, 0f32, SCREEN_WIDTH, SCREEN_HEIGHT))?;
+        let mut mods = mods::find_mods(ctx);
+        // a replay is played back with the mods and language it was recorded
+        // with, which are not saved
+        if let Some(ids) = replay.as_ref().and_then(|replay| replay.get_mods()) {
+            mods::set_enabled_mods(&mut mods, ids);
+        }
+        let assets = Assets::new(ctx, &resource_dirs, &mods)?;
+        let hot_reload = if config.dev_mode {
+            Some(HotReload::new(ctx, &assets, &resource_dirs))
+        } else {
+            None
+        };
+        let player = Rc::new(RefCell::new(Player::new(ctx, &assets, graphics::WHITE)?));
+        let mut settings = Settings::load(ctx);
+        if let Some(language) = replay.as_ref().and_then(|replay| replay.get_language()) {
+            settings.language = language.to_string();
+        }
+        // a replay brings the text speed it was recorded with
+        let mut config = config;
+        if replay.is_none() {
+            config.world.text_speed = settings.text_speed;
+        }
+        // the window options given on the command line win over the saved ones
+        // without being saved themselves
+        let mut window_settings = settings.clone();
+        if let Some(fullscreen) = config.fullscreen {
+            window_settings.fullscreen = fullscreen;
+        }
+        if let Some(resolution) = config.resolution {
+            window_settings.resolution = resolution;
+        }
+        let mut game = Game {
+            current_scene: GameStartScene::new_boxed(ctx, &assets, player.clone(), &settings)?,
             state: GameState::GameStart,
             player,
-            font,
+            assets,
+            resource_dirs,
+            mods: Rc::new(RefCell::new(mods)),
+            input_map: InputMap::with_keys(&settings.key_bindings),
+            settings: Rc::new(RefCell::new(settings)),
+            held_axes: HashMap::new(),
+            replay,
+            recorder,
+            hot_reload,
+            window_size: window_settings.resolution,
+            config,
         };
+        game.apply_window(ctx, &window_settings)?;
+        game.record(ReplayEvent::Options(game.config.world));
+        let language = game.settings.borrow().language.clone();
+        game.record(ReplayEvent::Language(language));
+        let ids = game
+            .mods
+            .borrow()
+            .iter()
+            .filter(|m| m.enabled)
+            .map(|m| m.id.clone())
+            .collect();
+        game.record(ReplayEvent::Mods(ids));
 
-        game
+        Ok(game)
+    }
+
+    fn to_screen(&self, x: f32, y: f32) -> (f32, f32) {
+        (
+            x * SCREEN_WIDTH / self.window_size.0,
+            y * SCREEN_HEIGHT / self.window_size.1,
+        )
+    }
+
+    fn record(&mut self, event: ReplayEvent) {
+        if let Some(recorder) = &mut self.recorder {
+            if let Err(e) = recorder.record(&event) {
+                println!("ERROR: Failed to record replay: {}", e);
+                self.recorder = None;
+            }
+        }
+    }
+
+    fn handle_input(&mut self, ctx: &mut Context, event: ReplayEvent) {
+        // live input is ignored while a replay is driving the game
+        if self.replay.is_some() {
+            return;
+        }
+        self.record(event.clone());
+        self.dispatch_input(ctx, event);
+    }
+
+    fn dispatch_input(&mut self, ctx: &mut Context, event: ReplayEvent) {
+        match event {
+            ReplayEvent::Step => (),
+            // whether the scene used it doesn't matter here, the action it
+            // would have been is recorded if it didn't
+            ReplayEvent::KeyDown(keycode) => {
+                self.current_scene.key_down_event(ctx, keycode);
+            }
+            ReplayEvent::ActionDown(action) => self.current_scene.action_down_event(ctx, action),
+            ReplayEvent::ActionUp(action) => self.current_scene.action_up_event(ctx, action),
+            ReplayEvent::MouseDown(button, x, y) => self
+                .current_scene
+                .mouse_button_down_event(ctx, button, x, y),
+            ReplayEvent::MouseUp(button, x, y) => {
+                self.current_scene.mouse_button_up_event(ctx, button, x, y)
+            }
+            ReplayEvent::Options(options) => self.config.world = options,
+            // taken from the start of the replay before the game is made
+            ReplayEvent::Language(_) | ReplayEvent::Mods(_) => (),
+        }
+    }
+
+    fn reload(&mut self, ctx: &mut Context, changed: &[(AssetKind, String)]) {
+        for (kind, name) in changed {
+            println!("Reloading {} \"{}\"", kind.get_name(), name);
+            self.assets.invalidate(*kind, name);
+        }
+        // a broken file is reported and the old one is kept, the game goes on
+        let result = match self.player.borrow_mut().reload(ctx, &self.assets) {
+            Ok(()) => self.current_scene.reload(ctx, &self.assets, changed),
+            Err(e) => Err(e),
+        };
+        if let Err(e) = result {
+            println!("ERROR: Failed to reload: {}", e);
+        }
+    }
+
+    // starts over with the assets of the mods picked in the mods menu
+    fn apply_mods(&mut self, ctx: &mut Context) -> GameResult<()> {
+        let mods = self.mods.borrow();
+        // a replay leaves what the player picked alone
+        if self.replay.is_none() {
+            if let Err(e) = mods::save_enabled_mods(ctx, &mods) {
+                println!("WARNING: Failed to save the enabled mods: {}", e);
+            }
+        }
+        self.assets = Assets::new(ctx, &self.resource_dirs, &mods)?;
+        self.player.borrow_mut().reload(ctx, &self.assets)?;
+        if self.hot_reload.is_some() {
+            self.hot_reload = Some(HotReload::new(ctx, &self.assets, &self.resource_dirs));
+        }
+        Ok(())
+    }
+
+    fn apply_window(&mut self, ctx: &mut Context, settings: &Settings) -> GameResult<()> {
+        let fullscreen_type = if settings.fullscreen {
+            FullscreenType::Desktop
+        } else {
+            FullscreenType::Windowed
+        };
+        graphics::set_fullscreen(ctx, fullscreen_type)?;
+        if !settings.fullscreen {
+            graphics::set_drawable_size(ctx, settings.resolution.0, settings.resolution.1)?;
+            self.window_size = settings.resolution;
+        }
+        Ok(())
+    }
+
+    // saves what was picked in the settings menu and uses it from now on
+    fn apply_settings(&mut self, ctx: &mut Context) -> GameResult<()> {
+        let settings = self.settings.borrow().clone();
+        if self.replay.is_none() {
+            if let Err(e) = settings.save(ctx) {
+                println!("WARNING: Failed to save the settings: {}", e);
+            }
+        }
+        self.input_map.set_keys(&settings.key_bindings);
+        // recorded so a replay picks up the new speed at the same point
+        if self.replay.is_none() && self.config.world.text_speed != settings.text_speed {
+            self.config.world.text_speed = settings.text_speed;
+            self.record(ReplayEvent::Options(self.config.world));
+        }
+        self.apply_window(ctx, &settings)
+    }
+
+    fn step(&mut self, ctx: &mut Context) -> GameResult<()> {
+        if let Some(replay) = &mut self.replay {
+            if let Some(inputs) = replay.next_step() {
+                for input in inputs {
+                    self.dispatch_input(ctx, input);
+                }
+            } else {
+                println!("Replay finished");
+                self.replay = None;
+            }
+        }
+        self.record(ReplayEvent::Step);
+
+        self.current_scene.update(ctx, FIXED_DT)?;
+        if self.current_scene.finished() {
+            let next_state = self
+                .current_scene
+                .get_next_state()
+                .unwrap_or_else(|| self.state.get_next_state());
+            match self.state {
+                GameState::Mods => self.apply_mods(ctx)?,
+                GameState::Settings => self.apply_settings(ctx)?,
+                _ => (),
+            }
+            self.state = next_state;
+            self.current_scene = self.state.get_scene(
+                ctx,
+                &self.assets,
+                self.player.clone(),
+                self.mods.clone(),
+                self.settings.clone(),
+                &self.config,
+            )?;
+        }
+        Ok(())
     }
 }
 
 impl EventHandler for Game {
     fn update(&mut self, ctx: &mut Context) -> GameResult<()> {
-        self.current_scene.update(ctx)?;
-        if self.current_scene.finished() {
-            self.state = self.state.get_next_state();
-            self.current_scene = self.state.get_scene(ctx, self.font, self.player.clone());
+        if let Some(hot_reload) = &mut self.hot_reload {
+            let changed = hot_reload.poll(timer::delta(ctx).as_secs_f32());
+            if !changed.is_empty() {
+                self.reload(ctx, &changed);
+            }
+        }
+        let mut steps = 0;
+        while timer::check_update_time(ctx, UPDATE_RATE) {
+            // drop time accumulated during a long hitch instead of fast
+            // forwarding through it
+            if steps < MAX_STEPS_PER_FRAME {
+                self.step(ctx)?;
+                steps += 1;
+            }
         }
         Ok(())
     }
@@ -72,32 +340,79 @@ impl EventHandler for Game {
     fn draw(&mut self, ctx: &mut Context) -> GameResult<()> {
         graphics::clear(ctx, graphics::BLACK);
 
-        self.current_scene.draw(ctx)?;
+        let alpha = (timer::remaining_update_time(ctx).as_secs_f32() / FIXED_DT).min(1f32);
+        self.current_scene.draw(ctx, alpha)?;
 
         graphics::present(ctx)
     }
 
+    fn resize_event(&mut self, _ctx: &mut Context, width: f32, height: f32) {
+        self.window_size = (width, height);
+    }
+
     fn mouse_button_down_event(&mut self, ctx: &mut Context, button: MouseButton, x: f32, y: f32) {
-        self.current_scene
-            .mouse_button_down_event(ctx, button, x, y);
+        let (x, y) = self.to_screen(x, y);
+        self.handle_input(ctx, ReplayEvent::MouseDown(button, x, y));
     }
 
     fn mouse_button_up_event(&mut self, ctx: &mut Context, button: MouseButton, x: f32, y: f32) {
-        self.current_scene.mouse_button_up_event(ctx, button, x, y);
+        let (x, y) = self.to_screen(x, y);
+        self.handle_input(ctx, ReplayEvent::MouseUp(button, x, y));
     }
 
     fn key_down_event(
         &mut self,
         ctx: &mut Context,
         keycode: KeyCode,
-        keymods: KeyMods,
+        _keymods: KeyMods,
         repeat: bool,
     ) {
-        self.current_scene
-            .key_down_event(ctx, keycode, keymods, repeat);
+        // live input is ignored while a replay is driving the game
+        if self.replay.is_some() {
+            return;
+        }
+        if !repeat {
+            self.record(ReplayEvent::KeyDown(keycode));
+            if self.current_scene.key_down_event(ctx, keycode) {
+                return;
+            }
+        }
+        if let Some(action) = self.input_map.get_key_action(keycode) {
+            self.handle_input(ctx, ReplayEvent::ActionDown(action));
+        }
+    }
+
+    fn key_up_event(&mut self, ctx: &mut Context, keycode: KeyCode, _keymods: KeyMods) {
+        if let Some(action) = self.input_map.get_key_action(keycode) {
+            self.handle_input(ctx, ReplayEvent::ActionUp(action));
+        }
+    }
+
+    fn gamepad_button_down_event(&mut self, ctx: &mut Context, btn: Button, _id: GamepadId) {
+        if let Some(action) = self.input_map.get_button_action(btn) {
+            self.handle_input(ctx, ReplayEvent::ActionDown(action));
+        }
+    }
+
+    fn gamepad_button_up_event(&mut self, ctx: &mut Context, btn: Button, _id: GamepadId) {
+        if let Some(action) = self.input_map.get_button_action(btn) {
+            self.handle_input(ctx, ReplayEvent::ActionUp(action));
+        }
     }
 
-    fn key_up_event(&mut self, ctx: &mut Context, keycode: KeyCode, keymods: KeyMods) {
-        self.current_scene.key_up_event(ctx, keycode, keymods);
+    fn gamepad_axis_event(&mut self, ctx: &mut Context, axis: Axis, value: f32, id: GamepadId) {
+        let action = self.input_map.get_axis_action(axis, value);
+        let previous = self.held_axes.get(&(id, axis)).copied();
+        if action == previous {
+            return;
+        }
+        if let Some(previous) = previous {
+            self.held_axes.remove(&(id, axis));
+            self.handle_input(ctx, ReplayEvent::ActionUp(previous));
+        }
+        if let Some(action) = action {
+            self.held_axes.insert((id, axis), action);
+            self.handle_input(ctx, ReplayEvent::ActionDown(action));
+        }
     }
 }
@@ -0,0 +1,260 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+
+use ld47_core::dialogue::Dialogue;
+use ld47_core::level::Level;
+use ld47_core::script::Script;
+use ld47_core::strings::Strings;
+
+use crate::assets::{self, AssetKind, AssetPaths, DEFAULT_LANGUAGE};
+use crate::mods::{self, Mod};
+use crate::resources::{self, ResourceDir};
+
+// the first copy of a file in the resources directories, in the order ggez
+// looks through them
+fn find_file(dirs: &[PathBuf], path: &str) -> Option<PathBuf> {
+    dirs.iter()
+        .map(|dir| dir.join(path.trim_start_matches('/')))
+        .find(|path| path.is_file())
+}
+
+// The conversations of the dialogue can only be about things in the level.
+// Files that can't be read are reported on their own.
+fn check_triggers(dirs: &[PathBuf], paths: &AssetPaths) -> Vec<(String, String)> {
+    let read = |kind: AssetKind| {
+        let path = paths.get(&(kind, String::from("main")))?;
+        let contents = fs::read_to_string(find_file(dirs, path)?).ok()?;
+        Some((path.clone(), contents))
+    };
+    let level = read(AssetKind::Level).and_then(|(_, contents)| Level::parse(&contents).ok());
+    let dialogue = read(AssetKind::Dialogue)
+        .and_then(|(path, contents)| Some((path, Dialogue::parse(&contents).ok()?)));
+    match (level, dialogue) {
+        (Some(level), Some((path, dialogue))) => dialogue
+            .get_missing_triggers(&level)
+            .into_iter()
+            .map(|missing| (path.clone(), missing))
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+// Reads the assets in `checked`, looking up what they refer to in `paths`, and
+// returns the problems found
+fn check_assets(
+    dirs: &[PathBuf],
+    paths: &AssetPaths,
+    checked: &AssetPaths,
+) -> Vec<(String, String)> {
+    let mut failed = Vec::new();
+    for ((kind, _), path) in checked {
+        let file = match find_file(dirs, path) {
+            Some(file) => file,
+            None => {
+                failed.push((path.clone(), String::from("not found")));
+                continue;
+            }
+        };
+        if *kind == AssetKind::Level {
+            match fs::read_to_string(&file).map(|contents| Level::parse(&contents)) {
+                Ok(Ok(level)) => {
+                    for sound in level.get_ambient_sounds() {
+                        if !paths.contains_key(&(AssetKind::Sound, sound.to_string())) {
+                            failed.push((
+                                path.clone(),
+                                format!("ambient sound \"{}\" is not in the manifest", sound),
+                            ));
+                        }
+                    }
+                }
+                Ok(Err(errors)) => {
+                    for error in errors {
+                        failed.push((path.clone(), error.to_string()));
+                    }
+                }
+                Err(e) => failed.push((path.clone(), e.to_string())),
+            }
+        }
+        if *kind == AssetKind::Dialogue {
+            match fs::read_to_string(&file).map(|contents| Dialogue::parse(&contents)) {
+                Ok(Ok(dialogue)) => {
+                    for image in dialogue.get_portraits() {
+                        if !paths.contains_key(&(AssetKind::Image, image.to_string())) {
+                            failed.push((
+                                path.clone(),
+                                format!("portrait \"{}\" is not in the manifest", image),
+                            ));
+                        }
+                    }
+                }
+                Ok(Err(errors)) => {
+                    for error in errors {
+                        failed.push((path.clone(), error.to_string()));
+                    }
+                }
+                Err(e) => failed.push((path.clone(), e.to_string())),
+            }
+        }
+    }
+
+    let mut languages: Vec<(&str, Strings)> = Vec::new();
+    for ((kind, name), path) in checked {
+        if *kind != AssetKind::Strings {
+            continue;
+        }
+        let contents = match find_file(dirs, path).map(fs::read_to_string) {
+            Some(Ok(contents)) => contents,
+            Some(Err(e)) => {
+                failed.push((path.clone(), e.to_string()));
+                continue;
+            }
+            None => continue,
+        };
+        match Strings::parse(&contents) {
+            Ok(strings) => {
+                let ids = Script::ALL
+                    .iter()
+                    .map(|script| format!("font.{}", script.get_name()))
+                    .chain(Some(String::from("font.default")));
+                for id in ids {
+                    let font = match strings.find(&id) {
+                        Some(font) => font,
+                        None => continue,
+                    };
+                    if !paths.contains_key(&(AssetKind::Font, font.to_string())) {
+                        failed.push((
+                            path.clone(),
+                            format!("font \"{}\" is not in the manifest", font),
+                        ));
+                    }
+                }
+                languages.push((name, strings));
+            }
+            Err(errors) => {
+                for error in errors {
+                    failed.push((path.clone(), error.to_string()));
+                }
+            }
+        }
+    }
+    let main = |kind: AssetKind| checked.contains_key(&(kind, String::from("main")));
+    if main(AssetKind::Level) || main(AssetKind::Dialogue) {
+        failed.extend(check_triggers(dirs, paths));
+    }
+
+    // English may not be one of the checked strings
+    let english = paths
+        .get(&(AssetKind::Strings, DEFAULT_LANGUAGE.to_string()))
+        .and_then(|path| find_file(dirs, path))
+        .and_then(|file| fs::read_to_string(file).ok())
+        .and_then(|contents| Strings::parse(&contents).ok());
+    if let Some(english) = english {
+        for (name, strings) in &languages {
+            let missing = strings.get_missing(&english);
+            if !missing.is_empty() {
+                println!(
+                    "WARNING: Strings \"{}\" are missing {} shown in English: {}",
+                    name,
+                    missing.len(),
+                    missing.join(", ")
+                );
+            }
+        }
+    }
+    failed
+}
+
+// Checks the entries of a mod laid on top of the base game on their own, the
+// same way they are checked when the mod is enabled
+fn check_mod(dirs: &[PathBuf], paths: &AssetPaths, m: &Mod) -> Vec<(String, String)> {
+    let mut failed = Vec::new();
+    let mut layered = paths.clone();
+    let mut checked = HashMap::new();
+    for entry in &m.entries {
+        let exists = paths.contains_key(&(entry.kind, entry.name.clone()));
+        if entry.replaces && !exists {
+            failed.push((
+                entry.path.clone(),
+                format!(
+                    "replaces {} \"{}\" which is not in the game",
+                    entry.kind.get_name(),
+                    entry.name
+                ),
+            ));
+        } else if !entry.replaces && exists {
+            failed.push((
+                entry.path.clone(),
+                format!(
+                    "adds {} \"{}\" which is already in the game",
+                    entry.kind.get_name(),
+                    entry.name
+                ),
+            ));
+        }
+        layered.insert((entry.kind, entry.name.clone()), entry.path.clone());
+        checked.insert((entry.kind, entry.name.clone()), entry.path.clone());
+    }
+    failed.extend(check_assets(dirs, &layered, &checked));
+    failed
+}
+
+// Checks that every asset in the manifest is there and that the levels,
+// dialogue and strings can be read and agree, without a window or audio
+// device. Every mod directory is checked on top of the base game as well,
+// enabled or not. Returns false if anything is wrong.
+// Ids another language leaves out are only warned about, as English is used.
+pub fn validate(resource_dirs: &[ResourceDir]) -> bool {
+    let mut dirs: Vec<PathBuf> = resource_dirs.iter().map(|dir| dir.path.clone()).collect();
+    if let Some(exe_dir) = resources::get_exe_resources_dir() {
+        dirs.insert(0, exe_dir);
+    }
+
+    let manifest =
+        match find_file(&dirs, assets::MANIFEST).and_then(|path| fs::read_to_string(path).ok()) {
+            Some(manifest) => manifest,
+            None => {
+                println!("ERROR: Failed to read {}", assets::MANIFEST);
+                return false;
+            }
+        };
+
+    let (paths, mut failed) = assets::parse_manifest(&manifest);
+    if !paths.contains_key(&(AssetKind::Strings, DEFAULT_LANGUAGE.to_string())) {
+        failed.push((
+            String::from(assets::MANIFEST),
+            format!("strings \"{}\" are not in the manifest", DEFAULT_LANGUAGE),
+        ));
+    }
+    failed.extend(check_assets(&dirs, &paths, &paths));
+
+    let mod_dirs = mods::find_mod_dirs(&dirs);
+    for (id, manifest_path) in &mod_dirs {
+        let parsed = fs::read_to_string(manifest_path)
+            .map_err(|e| e.to_string())
+            .and_then(|contents| Mod::parse(id, &contents));
+        let problems = match parsed {
+            Ok(m) => check_mod(&dirs, &paths, &m),
+            Err(e) => vec![(manifest_path.display().to_string(), e)],
+        };
+        for (name, reason) in problems {
+            failed.push((format!("mod \"{}\": {}", id, name), reason));
+        }
+    }
+
+    if failed.is_empty() {
+        println!(
+            "All {} assets and {} mods are there",
+            paths.len(),
+            mod_dirs.len()
+        );
+        true
+    } else {
+        failed.sort();
+        println!("ERROR: Found {} problems with the resources:", failed.len());
+        for (name, reason) in failed {
+            println!("    {} ({})", name, reason);
+        }
+        false
+    }
+}
@@ -0,0 +1,84 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use ggez::Context;
+
+use crate::assets::{AssetKind, Assets};
+use crate::resources::{self, ResourceDir};
+
+const POLL_INTERVAL: f32 = 0.5f32;
+
+struct WatchedFile {
+    kind: AssetKind,
+    name: String,
+    path: PathBuf,
+    modified: Option<SystemTime>,
+}
+
+// Checks the files in the manifest for changes every so often while in dev
+// mode, so they can be swapped into the running game
+pub struct HotReload {
+    files: Vec<WatchedFile>,
+    timer: f32,
+}
+
+fn get_modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+impl HotReload {
+    pub fn new(ctx: &Context, assets: &Assets, resource_dirs: &[ResourceDir]) -> Self {
+        let search_paths = resources::get_search_paths(ctx, resource_dirs);
+        let mut files = Vec::new();
+        for (kind, name, path) in assets.get_files() {
+            // fonts are baked into text that is only built once
+            if kind == AssetKind::Font {
+                continue;
+            }
+            // the same file ggez would open, the first one found in search order
+            let relative = path.trim_start_matches('/');
+            match search_paths
+                .iter()
+                .map(|dir| dir.join(relative))
+                .find(|path| path.is_file())
+            {
+                Some(path) => files.push(WatchedFile {
+                    kind,
+                    name: name.to_string(),
+                    modified: get_modified(&path),
+                    path,
+                }),
+                None => println!(
+                    "WARNING: Can't watch \"{}\" for changes, it is not in a resources directory",
+                    path
+                ),
+            }
+        }
+        println!("Watching {} files for changes", files.len());
+
+        Self {
+            files,
+            timer: POLL_INTERVAL,
+        }
+    }
+
+    // (kind, name) of every file that changed since the last poll
+    pub fn poll(&mut self, dt: f32) -> Vec<(AssetKind, String)> {
+        self.timer -= dt;
+        if self.timer > 0f32 {
+            return Vec::new();
+        }
+        self.timer = POLL_INTERVAL;
+
+        let mut changed = Vec::new();
+        for file in &mut self.files {
+            let modified = get_modified(&file.path);
+            if modified != file.modified {
+                file.modified = modified;
+                changed.push((file.kind, file.name.clone()));
+            }
+        }
+        changed
+    }
+}
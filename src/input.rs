@@ -0,0 +1,191 @@
+use std::collections::HashMap;
+use std::mem;
+
+use ggez::event::{Axis, Button};
+use ggez::input::keyboard::KeyCode;
+
+pub use ld47_core::input::Action;
+
+const AXIS_DEADZONE: f32 = 0.5f32;
+
+pub struct InputMap {
+    keys: HashMap<KeyCode, Action>,
+    buttons: HashMap<Button, Action>,
+}
+
+// the keys that can be bound in the settings, by the name they are saved with
+const KEY_NAMES: [(KeyCode, &str); 61] = [
+    (KeyCode::A, "A"),
+    (KeyCode::B, "B"),
+    (KeyCode::C, "C"),
+    (KeyCode::D, "D"),
+    (KeyCode::E, "E"),
+    (KeyCode::F, "F"),
+    (KeyCode::G, "G"),
+    (KeyCode::H, "H"),
+    (KeyCode::I, "I"),
+    (KeyCode::J, "J"),
+    (KeyCode::K, "K"),
+    (KeyCode::L, "L"),
+    (KeyCode::M, "M"),
+    (KeyCode::N, "N"),
+    (KeyCode::O, "O"),
+    (KeyCode::P, "P"),
+    (KeyCode::Q, "Q"),
+    (KeyCode::R, "R"),
+    (KeyCode::S, "S"),
+    (KeyCode::T, "T"),
+    (KeyCode::U, "U"),
+    (KeyCode::V, "V"),
+    (KeyCode::W, "W"),
+    (KeyCode::X, "X"),
+    (KeyCode::Y, "Y"),
+    (KeyCode::Z, "Z"),
+    (KeyCode::Key0, "0"),
+    (KeyCode::Key1, "1"),
+    (KeyCode::Key2, "2"),
+    (KeyCode::Key3, "3"),
+    (KeyCode::Key4, "4"),
+    (KeyCode::Key5, "5"),
+    (KeyCode::Key6, "6"),
+    (KeyCode::Key7, "7"),
+    (KeyCode::Key8, "8"),
+    (KeyCode::Key9, "9"),
+    (KeyCode::Left, "Left"),
+    (KeyCode::Right, "Right"),
+    (KeyCode::Up, "Up"),
+    (KeyCode::Down, "Down"),
+    (KeyCode::Space, "Space"),
+    (KeyCode::Return, "Return"),
+    (KeyCode::Escape, "Escape"),
+    (KeyCode::Back, "Backspace"),
+    (KeyCode::Tab, "Tab"),
+    (KeyCode::LShift, "LShift"),
+    (KeyCode::RShift, "RShift"),
+    (KeyCode::LControl, "LControl"),
+    (KeyCode::RControl, "RControl"),
+    (KeyCode::LAlt, "LAlt"),
+    (KeyCode::RAlt, "RAlt"),
+    (KeyCode::Insert, "Insert"),
+    (KeyCode::Delete, "Delete"),
+    (KeyCode::Home, "Home"),
+    (KeyCode::End, "End"),
+    (KeyCode::PageUp, "PageUp"),
+    (KeyCode::PageDown, "PageDown"),
+    (KeyCode::Comma, "Comma"),
+    (KeyCode::Period, "Period"),
+    (KeyCode::Slash, "Slash"),
+    (KeyCode::Semicolon, "Semicolon"),
+];
+
+// Keys without a name are kept in replays by their code. The codes run from
+// the first key to the last one, Cut, so every code up to it is a key.
+pub fn get_key_code(keycode: KeyCode) -> u32 {
+    keycode as u32
+}
+
+pub fn key_from_code(code: u32) -> Option<KeyCode> {
+    if code <= KeyCode::Cut as u32 {
+        Some(unsafe { mem::transmute::<u32, KeyCode>(code) })
+    } else {
+        None
+    }
+}
+
+pub fn get_key_name(keycode: KeyCode) -> Option<&'static str> {
+    KEY_NAMES
+        .iter()
+        .find(|(key, _)| *key == keycode)
+        .map(|(_, name)| *name)
+}
+
+pub fn key_from_name(name: &str) -> Option<KeyCode> {
+    KEY_NAMES
+        .iter()
+        .find(|(_, key_name)| *key_name == name)
+        .map(|(key, _)| *key)
+}
+
+pub fn get_default_keys() -> Vec<(KeyCode, Action)> {
+    vec![
+        (KeyCode::A, Action::Left),
+        (KeyCode::Left, Action::Left),
+        (KeyCode::D, Action::Right),
+        (KeyCode::Right, Action::Right),
+        (KeyCode::W, Action::Up),
+        (KeyCode::Up, Action::Up),
+        (KeyCode::S, Action::Down),
+        (KeyCode::Down, Action::Down),
+        (KeyCode::E, Action::Interact),
+        (KeyCode::Space, Action::Interact),
+        (KeyCode::Return, Action::Interact),
+        (KeyCode::Escape, Action::Back),
+    ]
+}
+
+impl InputMap {
+    pub fn new() -> Self {
+        Self::with_keys(&get_default_keys())
+    }
+
+    pub fn with_keys(keys: &[(KeyCode, Action)]) -> Self {
+        let mut buttons = HashMap::new();
+        buttons.insert(Button::DPadLeft, Action::Left);
+        buttons.insert(Button::DPadRight, Action::Right);
+        buttons.insert(Button::DPadUp, Action::Up);
+        buttons.insert(Button::DPadDown, Action::Down);
+        buttons.insert(Button::South, Action::Interact);
+        buttons.insert(Button::North, Action::EnterDoor);
+        buttons.insert(Button::East, Action::Back);
+        buttons.insert(Button::Start, Action::Interact);
+
+        let mut map = Self {
+            keys: HashMap::new(),
+            buttons,
+        };
+        map.set_keys(keys);
+        map
+    }
+
+    pub fn set_keys(&mut self, keys: &[(KeyCode, Action)]) {
+        self.keys = keys.iter().copied().collect();
+    }
+
+    pub fn get_key_action(&self, keycode: KeyCode) -> Option<Action> {
+        self.keys.get(&keycode).copied()
+    }
+
+    pub fn get_button_action(&self, button: Button) -> Option<Action> {
+        self.buttons.get(&button).copied()
+    }
+
+    // gilrs reports positive y as up on both sticks and the d-pad
+    pub fn get_axis_action(&self, axis: Axis, value: f32) -> Option<Action> {
+        if value.abs() < AXIS_DEADZONE {
+            return None;
+        }
+        match axis {
+            Axis::LeftStickX | Axis::DPadX => {
+                if value < 0f32 {
+                    Some(Action::Left)
+                } else {
+                    Some(Action::Right)
+                }
+            }
+            Axis::LeftStickY | Axis::DPadY => {
+                if value > 0f32 {
+                    Some(Action::Up)
+                } else {
+                    Some(Action::Down)
+                }
+            }
+            _ => None,
+        }
+    }
+}
+
+impl Default for InputMap {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -1,150 +1,61 @@
 use ggez::graphics::{self, Color, DrawMode, DrawParam, Mesh, Rect};
 use ggez::{Context, GameResult};
 
-use crate::scenes::mainscene::PuzzleID;
+use ld47_core::interactable::{Interactable, InteractableType};
+use ld47_core::puzzle::PuzzleID;
 
-const DEFAULT_RADIUS: f32 = 70f32;
+use crate::palette::{self, Palette};
 
-#[derive(Copy, Clone, PartialEq)]
-pub enum InteractableType {
-    Door(usize),
-    LockedDoor(usize, bool),
-    Puzzle(PuzzleID, bool),
-    Ship,
+fn draw_panel(ctx: &mut Context, rect: Rect, color: Color) -> GameResult<()> {
+    let panel_mesh = Mesh::new_rectangle(ctx, DrawMode::fill(), rect, color)?;
+    graphics::draw(ctx, &panel_mesh, DrawParam::new())
 }
 
-pub struct Interactable {
-    itype: InteractableType,
-    x: f32,
-    y: f32,
-    radius: f32,
-}
-
-impl Interactable {
-    pub fn new(itype: InteractableType, x: f32, y: f32) -> Self {
-        Self {
-            itype,
-            x,
-            y,
-            radius: DEFAULT_RADIUS,
+pub fn draw(ctx: &mut Context, interactable: &Interactable, palette: &Palette) -> GameResult<()> {
+    let (x, y) = (interactable.get_x(), interactable.get_y());
+    match interactable.get_type() {
+        InteractableType::Door(_) => {
+            draw_panel(
+                ctx,
+                Rect::new(x - 7f32, y - 8f32, 14f32, 16f32),
+                palette.door,
+            )?;
         }
-    }
-
-    pub fn is_within_range(&self, x: f32, y: f32) -> bool {
-        let a = self.x - x;
-        let b = self.y - y;
-        (a * a + b * b).sqrt() <= self.radius
-    }
-
-    pub fn get_type(&self) -> InteractableType {
-        self.itype
-    }
-
-    pub fn get_radius(&mut self) -> f32 {
-        self.radius
-    }
-
-    pub fn set_radius(&mut self, radius: f32) {
-        self.radius = radius;
-    }
-
-    pub fn get_x(&self) -> f32 {
-        self.x
-    }
-
-    pub fn get_y(&self) -> f32 {
-        self.y
-    }
-
-    pub fn draw(&self, ctx: &mut Context) -> GameResult<()> {
-        match self.itype {
-            InteractableType::Door(_) => {
-                let panel_mesh = Mesh::new_rectangle(
-                    ctx,
-                    DrawMode::fill(),
-                    Rect::new(0f32, 0f32, 14f32, 16f32),
-                    Color::from_rgb(0x16, 0x9c, 0xd8),
-                )?;
-                graphics::draw(
-                    ctx,
-                    &panel_mesh,
-                    DrawParam::new().dest([self.x - 7f32, self.y - 8f32]),
-                )?;
+        InteractableType::LockedDoor(_, unlocked) => {
+            let rect = Rect::new(x - 7f32, y - 8f32, 14f32, 16f32);
+            if unlocked {
+                draw_panel(ctx, rect, palette.door)?;
+            } else {
+                draw_panel(ctx, rect, palette.door_locked)?;
+                palette::draw_lock(ctx, rect, palette.door_locked)?;
             }
-            InteractableType::LockedDoor(_, unlocked) => {
-                let color;
-                if unlocked {
-                    color = Color::from_rgb(0x16, 0x9c, 0xd8);
-                } else {
-                    color = Color::from_rgb(0xdf, 0, 0);
-                }
-                let panel_mesh = Mesh::new_rectangle(
-                    ctx,
-                    DrawMode::fill(),
-                    Rect::new(0f32, 0f32, 14f32, 16f32),
-                    color,
-                )?;
-                graphics::draw(
-                    ctx,
-                    &panel_mesh,
-                    DrawParam::new().dest([self.x - 7f32, self.y - 8f32]),
-                )?;
+        }
+        InteractableType::Puzzle(id, cleared) => {
+            let rect = match id {
+                PuzzleID::FarRightHall => Rect::new(x - 20f32, y - 15f32, 40f32, 30f32),
+                PuzzleID::Computer => Rect::new(x - 200f32, y - 150f32, 400f32, 300f32),
+            };
+            if cleared {
+                draw_panel(ctx, rect, palette.panel_cleared)?;
+                palette::draw_check(ctx, rect, palette.panel_cleared)?;
+            } else {
+                draw_panel(ctx, rect, palette.panel_uncleared)?;
+                palette::draw_lock(ctx, rect, palette.panel_uncleared)?;
             }
-            InteractableType::Puzzle(id, cleared) => match id {
-                PuzzleID::FarRightHall => {
-                    let color;
-                    if cleared {
-                        color = Color::from_rgb(0x3f, 0xf8, 0x4c);
-                    } else {
-                        color = Color::from_rgb(0xef, 0, 0);
-                    }
-                    let panel_mesh = Mesh::new_rectangle(
-                        ctx,
-                        DrawMode::fill(),
-                        Rect::new(0f32, 0f32, 40f32, 30f32),
-                        color,
-                    )?;
-                    graphics::draw(
-                        ctx,
-                        &panel_mesh,
-                        DrawParam::new().dest([self.x - 20f32, self.y - 15f32]),
-                    )?;
-                }
-                PuzzleID::Computer => {
-                    let color;
-                    if cleared {
-                        color = Color::from_rgb(0x3f, 0xf8, 0x4c);
-                    } else {
-                        color = Color::from_rgb(0xef, 0, 0);
-                    }
-                    let panel_mesh = Mesh::new_rectangle(
-                        ctx,
-                        DrawMode::fill(),
-                        Rect::new(0f32, 0f32, 400f32, 300f32),
-                        color,
-                    )?;
-                    graphics::draw(
-                        ctx,
-                        &panel_mesh,
-                        DrawParam::new().dest([self.x - 200f32, self.y - 150f32]),
-                    )?;
-                }
-            },
-            InteractableType::Ship => {}
         }
-
-        Ok(())
-    }
-
-    pub fn set_unlocked(&mut self, unlocked: bool) {
-        if let InteractableType::LockedDoor(_, is_unlocked) = &mut self.itype {
-            *is_unlocked = unlocked;
+        InteractableType::Ship => {}
+        InteractableType::Examine => {
+            let mesh = Mesh::new_circle(
+                ctx,
+                DrawMode::stroke(2f32),
+                [x, y],
+                6f32,
+                0.5f32,
+                Color::new(1f32, 1f32, 1f32, 0.4f32),
+            )?;
+            graphics::draw(ctx, &mesh, DrawParam::new())?;
         }
     }
 
-    pub fn set_puzzle_cleared(&mut self, cleared: bool) {
-        if let InteractableType::Puzzle(_, is_cleared) = &mut self.itype {
-            *is_cleared = cleared;
-        }
-    }
+    Ok(())
 }
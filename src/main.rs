@@ -1,24 +1,121 @@
+mod assets;
+mod audio;
+mod captions;
+mod config;
+mod dialogue;
 mod door;
+mod fonts;
 mod game;
+mod headless;
+mod hotreload;
+mod input;
 mod interactable;
+mod mods;
+mod palette;
 mod player;
 mod puzzle;
+mod replay;
+mod resources;
+mod save;
 mod scenes;
+mod settings;
+
+use std::process;
 
 use ggez::conf::WindowSetup;
 use ggez::{event, ContextBuilder};
 
 fn main() {
-    let (mut ctx, mut event_loop) = ContextBuilder::new("ld47_stuckinaloop", "Stephen Seo")
-        .window_setup(
-            WindowSetup::default()
-                .title("LudumDare 47 - Stuck In A Loop - Escape")
-                .vsync(true),
-        )
-        .build()
-        .unwrap();
-
-    let mut game = game::Game::new(&mut ctx);
+    let mut config = match config::Config::from_args(std::env::args().skip(1)) {
+        Ok(config) => config,
+        Err(e) => {
+            println!("ERROR: {}\n\n{}", e, config::USAGE);
+            process::exit(2);
+        }
+    };
+    if config.help {
+        println!("{}", config::USAGE);
+        return;
+    }
+
+    let resource_dirs = resources::find_resource_dirs(config.resources.as_deref());
+    if config.headless {
+        if !headless::validate(&resource_dirs) {
+            process::exit(1);
+        }
+        return;
+    }
+
+    let replay = match config.replay.clone() {
+        Some(path) => match replay::Replay::load(&path) {
+            Ok(replay) => {
+                // played back the way it was recorded, whatever is given now
+                match replay.get_options() {
+                    Some(options) => config.world = options,
+                    None => println!(
+                        "WARNING: Replay \"{}\" has no options, using the ones given",
+                        path
+                    ),
+                }
+                Some(replay)
+            }
+            Err(e) => {
+                println!("ERROR: Failed to load replay \"{}\": {}", path, e);
+                return;
+            }
+        },
+        None => None,
+    };
+    let recorder = match &config.record {
+        Some(path) => match replay::Recorder::new(path) {
+            Ok(recorder) => Some(recorder),
+            Err(e) => {
+                println!("ERROR: Failed to create replay file \"{}\": {}", path, e);
+                return;
+            }
+        },
+        None => None,
+    };
+
+    // the window is sized by the game once the saved settings can be read
+    let mut builder = ContextBuilder::new("ld47_stuckinaloop", "Stephen Seo").window_setup(
+        WindowSetup::default()
+            .title("LudumDare 47 - Stuck In A Loop - Escape")
+            .vsync(true),
+    );
+    for dir in &resource_dirs {
+        builder = builder.add_resource_path(&dir.path);
+    }
+    for path in mods::find_mod_packs(&resource_dirs) {
+        match std::fs::read(&path) {
+            Ok(bytes) => {
+                println!("Adding mod pack \"{}\"", path.display());
+                builder = builder.add_zipfile_bytes(bytes);
+            }
+            Err(e) => println!(
+                "WARNING: Failed to read mod pack \"{}\": {}",
+                path.display(),
+                e
+            ),
+        }
+    }
+
+    let (mut ctx, mut event_loop) = match builder.build() {
+        Ok(context) => context,
+        Err(e) => {
+            println!("ERROR: Failed to create the game window: {}", e);
+            return;
+        }
+    };
+
+    resources::log_resource_dir(&ctx, &resource_dirs);
+    let mut game = match game::Game::new(&mut ctx, config, resource_dirs, replay, recorder) {
+        Ok(game) => game,
+        Err(e) => {
+            println!("ERROR: {}", e);
+            return;
+        }
+    };
 
     match event::run(&mut ctx, &mut event_loop, &mut game) {
         Ok(_) => println!("Exited cleanly"),
@@ -0,0 +1,226 @@
+use std::ffi::OsStr;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+use ggez::{filesystem, Context};
+
+use crate::assets::{self, AssetKind};
+use crate::resources::{self, ResourceDir};
+
+pub const MODS_DIR: &str = "/mods";
+const MOD_MANIFEST: &str = "mod.manifest";
+// ids of the enabled mods, in the user config directory
+const ENABLED_MODS_FILE: &str = "mods.txt";
+
+#[derive(Clone, Debug)]
+pub struct ModEntry {
+    pub kind: AssetKind,
+    pub name: String,
+    pub path: String,
+    // replaces an asset of the base game instead of adding a new one
+    pub replaces: bool,
+}
+
+// A directory in /mods with a manifest of the assets it replaces or adds,
+// laid on top of the base game when enabled
+#[derive(Clone, Debug)]
+pub struct Mod {
+    pub id: String,
+    pub name: String,
+    pub version: String,
+    pub entries: Vec<ModEntry>,
+    pub enabled: bool,
+}
+
+impl Mod {
+    fn load(ctx: &mut Context, id: &str) -> Result<Mod, String> {
+        let manifest_path = format!("{}/{}/{}", MODS_DIR, id, MOD_MANIFEST);
+        let contents = assets::read_to_string(ctx, &manifest_path).map_err(|e| e.to_string())?;
+        Mod::parse(id, &contents)
+    }
+
+    pub fn parse(id: &str, contents: &str) -> Result<Mod, String> {
+        let dir = format!("{}/{}", MODS_DIR, id);
+        let mut name = None;
+        let mut version = None;
+        let mut entries = Vec::new();
+        for (idx, line) in contents.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let words: Vec<&str> = line.split_whitespace().collect();
+            match (words[0], words.len()) {
+                ("name", _) => name = Some(line["name".len()..].trim().to_string()),
+                ("version", _) => version = Some(line["version".len()..].trim().to_string()),
+                ("replace", 4) | ("add", 4) => match AssetKind::from_name(words[1]) {
+                    Some(kind) => entries.push(ModEntry {
+                        kind,
+                        name: words[2].to_string(),
+                        path: format!("{}/{}", dir, words[3].trim_start_matches('/')),
+                        replaces: words[0] == "replace",
+                    }),
+                    None => {
+                        return Err(format!(
+                            "{} line {}: unknown kind \"{}\"",
+                            MOD_MANIFEST,
+                            idx + 1,
+                            words[1]
+                        ))
+                    }
+                },
+                _ => {
+                    return Err(format!(
+                        "{} line {}: invalid entry \"{}\"",
+                        MOD_MANIFEST,
+                        idx + 1,
+                        line
+                    ))
+                }
+            }
+        }
+
+        match (name, version) {
+            (Some(name), Some(version)) if !name.is_empty() && !version.is_empty() => Ok(Mod {
+                id: id.to_string(),
+                name,
+                version,
+                entries,
+                enabled: false,
+            }),
+            _ => Err(format!("{} needs a name and a version", MOD_MANIFEST)),
+        }
+    }
+}
+
+fn get_enabled_mods_path(ctx: &Context) -> PathBuf {
+    filesystem::user_config_dir(ctx).join(ENABLED_MODS_FILE)
+}
+
+// Every mod in /mods of any resources directory or zip pack. The enabled ones
+// come first, in the order they are applied, so later ones win.
+pub fn find_mods(ctx: &mut Context) -> Vec<Mod> {
+    let mut ids = Vec::new();
+    if filesystem::is_dir(ctx, MODS_DIR) {
+        match filesystem::read_dir(ctx, MODS_DIR) {
+            Ok(dirs) => {
+                for dir in dirs {
+                    if let Some(id) = dir.file_name().and_then(|name| name.to_str()) {
+                        ids.push(id.to_string());
+                    }
+                }
+            }
+            Err(e) => println!("WARNING: Failed to look for mods: {}", e),
+        }
+    }
+    // the same mod may be in more than one resources directory
+    ids.sort();
+    ids.dedup();
+
+    let mut mods = Vec::new();
+    for id in ids {
+        if !filesystem::is_dir(ctx, format!("{}/{}", MODS_DIR, id)) {
+            continue;
+        }
+        match Mod::load(ctx, &id) {
+            Ok(m) => mods.push(m),
+            Err(e) => println!("WARNING: Skipping mod \"{}\": {}", id, e),
+        }
+    }
+
+    let enabled = fs::read_to_string(get_enabled_mods_path(ctx)).unwrap_or_default();
+    let ids: Vec<String> = enabled
+        .lines()
+        .map(str::trim)
+        .filter(|id| !id.is_empty())
+        .map(str::to_string)
+        .collect();
+    set_enabled_mods(&mut mods, &ids);
+    mods
+}
+
+// Enables only the mods with the ids and moves them to the front, in the
+// order given
+pub fn set_enabled_mods(mods: &mut Vec<Mod>, ids: &[String]) {
+    let mut rest = std::mem::take(mods);
+    for m in &mut rest {
+        m.enabled = false;
+    }
+    for id in ids {
+        match rest.iter().position(|m| m.id == *id) {
+            Some(idx) => {
+                let mut m = rest.remove(idx);
+                m.enabled = true;
+                mods.push(m);
+            }
+            None => println!("WARNING: Enabled mod \"{}\" was not found", id),
+        }
+    }
+    mods.extend(rest);
+}
+
+pub fn save_enabled_mods(ctx: &Context, mods: &[Mod]) -> io::Result<()> {
+    let path = get_enabled_mods_path(ctx);
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let mut contents = String::new();
+    for m in mods.iter().filter(|m| m.enabled) {
+        contents.push_str(&m.id);
+        contents.push('\n');
+    }
+    fs::write(path, contents)
+}
+
+// The mod directories in the resource directories, without a context. Zip
+// packs are left out.
+pub fn find_mod_dirs(dirs: &[PathBuf]) -> Vec<(String, PathBuf)> {
+    let mut mods: Vec<(String, PathBuf)> = Vec::new();
+    for dir in dirs {
+        let entries = match fs::read_dir(dir.join("mods")) {
+            Ok(entries) => entries,
+            Err(_) => continue,
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let id = match path.file_name().and_then(|name| name.to_str()) {
+                Some(id) if path.is_dir() => id.to_string(),
+                _ => continue,
+            };
+            // the first directory wins, like in ggez
+            if !mods.iter().any(|(other, _)| *other == id) {
+                mods.push((id, path.join(MOD_MANIFEST)));
+            }
+        }
+    }
+    mods.sort();
+    mods
+}
+
+// Zip packs have to be added before the context is built, so they are looked
+// for in the "mods" directories of the resource directories. The files in a
+// pack start with mods/<id>/, the same as a mod directory.
+pub fn find_mod_packs(resource_dirs: &[ResourceDir]) -> Vec<PathBuf> {
+    let mut dirs: Vec<PathBuf> = resource_dirs.iter().map(|dir| dir.path.clone()).collect();
+    if let Some(exe_dir) = resources::get_exe_resources_dir() {
+        dirs.insert(0, exe_dir);
+    }
+
+    let mut packs = Vec::new();
+    for dir in dirs {
+        let entries = match fs::read_dir(dir.join("mods")) {
+            Ok(entries) => entries,
+            Err(_) => continue,
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.is_file() && path.extension() == Some(OsStr::new("zip")) {
+                packs.push(path);
+            }
+        }
+    }
+    packs.sort();
+    packs.dedup();
+    packs
+}
@@ -0,0 +1,142 @@
+use ggez::graphics::{self, Color, DrawMode, DrawParam, Mesh, Rect};
+use ggez::{Context, GameResult};
+
+use crate::settings::ColorblindMode;
+
+// The colors that tell puzzle tiles and panels apart. Every state also has a
+// shape drawn over it so none of them depend on color alone.
+#[derive(Copy, Clone, Debug)]
+pub struct Palette {
+    pub puzzle_background: Color,
+    pub tile_on: Color,
+    pub tile_off: Color,
+    // the string id of what the info text calls the tiles that are on
+    pub tile_on_name: &'static str,
+    pub door: Color,
+    pub door_locked: Color,
+    pub panel_cleared: Color,
+    pub panel_uncleared: Color,
+}
+
+impl Palette {
+    pub fn new(mode: ColorblindMode) -> Self {
+        match mode {
+            ColorblindMode::Off => Self {
+                puzzle_background: Color::from_rgb(0x29, 0x8d, 0xff),
+                tile_on: Color::from_rgb(0, 0xff, 0),
+                tile_off: Color::from_rgb(0xff, 0, 0),
+                tile_on_name: "color.green",
+                door: Color::from_rgb(0x16, 0x9c, 0xd8),
+                door_locked: Color::from_rgb(0xdf, 0, 0),
+                panel_cleared: Color::from_rgb(0x3f, 0xf8, 0x4c),
+                panel_uncleared: Color::from_rgb(0xef, 0, 0),
+            },
+            // blue against orange, which red-green colorblindness keeps apart
+            ColorblindMode::Deuteranopia => Self {
+                puzzle_background: Color::from_rgb(0x40, 0x40, 0x48),
+                tile_on: Color::from_rgb(0x56, 0xb4, 0xe9),
+                tile_off: Color::from_rgb(0xe6, 0x9f, 0),
+                tile_on_name: "color.blue",
+                door: Color::from_rgb(0x56, 0xb4, 0xe9),
+                door_locked: Color::from_rgb(0xe6, 0x9f, 0),
+                panel_cleared: Color::from_rgb(0x56, 0xb4, 0xe9),
+                panel_uncleared: Color::from_rgb(0xe6, 0x9f, 0),
+            },
+            // reds look dark with protanopia, so the warning color is yellow
+            ColorblindMode::Protanopia => Self {
+                puzzle_background: Color::from_rgb(0x40, 0x40, 0x48),
+                tile_on: Color::from_rgb(0, 0x72, 0xb2),
+                tile_off: Color::from_rgb(0xf0, 0xe4, 0x42),
+                tile_on_name: "color.blue",
+                door: Color::from_rgb(0, 0x72, 0xb2),
+                door_locked: Color::from_rgb(0xf0, 0xe4, 0x42),
+                panel_cleared: Color::from_rgb(0, 0x72, 0xb2),
+                panel_uncleared: Color::from_rgb(0xf0, 0xe4, 0x42),
+            },
+            // blue and green are what tritanopia mixes up, red and cyan stay apart
+            ColorblindMode::Tritanopia => Self {
+                puzzle_background: Color::from_rgb(0x40, 0x40, 0x48),
+                tile_on: Color::from_rgb(0x40, 0xd0, 0xd0),
+                tile_off: Color::from_rgb(0xd5, 0, 0x32),
+                tile_on_name: "color.cyan",
+                door: Color::from_rgb(0x40, 0xd0, 0xd0),
+                door_locked: Color::from_rgb(0xd5, 0, 0x32),
+                panel_cleared: Color::from_rgb(0x40, 0xd0, 0xd0),
+                panel_uncleared: Color::from_rgb(0xd5, 0, 0x32),
+            },
+            ColorblindMode::HighContrast => Self {
+                puzzle_background: Color::from_rgb(0x80, 0x80, 0x80),
+                tile_on: graphics::WHITE,
+                tile_off: graphics::BLACK,
+                tile_on_name: "color.white",
+                door: graphics::WHITE,
+                door_locked: graphics::BLACK,
+                panel_cleared: graphics::WHITE,
+                panel_uncleared: graphics::BLACK,
+            },
+        }
+    }
+}
+
+// shapes are drawn in black on light colors and white on dark ones
+fn get_shape_color(background: Color) -> Color {
+    if 0.299f32 * background.r + 0.587f32 * background.g + 0.114f32 * background.b > 0.5f32 {
+        graphics::BLACK
+    } else {
+        graphics::WHITE
+    }
+}
+
+// the shapes fit inside rect, which is filled with the background color
+pub fn draw_check(ctx: &mut Context, rect: Rect, background: Color) -> GameResult<()> {
+    let line = Mesh::new_line(
+        ctx,
+        &[
+            [rect.x + rect.w * 0.2f32, rect.y + rect.h * 0.55f32],
+            [rect.x + rect.w * 0.4f32, rect.y + rect.h * 0.75f32],
+            [rect.x + rect.w * 0.8f32, rect.y + rect.h * 0.25f32],
+        ],
+        rect.w.min(rect.h) * 0.12f32,
+        get_shape_color(background),
+    )?;
+    graphics::draw(ctx, &line, DrawParam::new())
+}
+
+pub fn draw_cross(ctx: &mut Context, rect: Rect, background: Color) -> GameResult<()> {
+    let color = get_shape_color(background);
+    let width = rect.w.min(rect.h) * 0.12f32;
+    let (left, right) = (rect.x + rect.w * 0.25f32, rect.x + rect.w * 0.75f32);
+    let (top, bottom) = (rect.y + rect.h * 0.25f32, rect.y + rect.h * 0.75f32);
+    let line = Mesh::new_line(ctx, &[[left, top], [right, bottom]], width, color)?;
+    graphics::draw(ctx, &line, DrawParam::new())?;
+    let line = Mesh::new_line(ctx, &[[right, top], [left, bottom]], width, color)?;
+    graphics::draw(ctx, &line, DrawParam::new())
+}
+
+pub fn draw_lock(ctx: &mut Context, rect: Rect, background: Color) -> GameResult<()> {
+    let color = get_shape_color(background);
+    let size = rect.w.min(rect.h) * 0.7f32;
+    let (x, y) = (
+        rect.x + (rect.w - size) / 2f32,
+        rect.y + (rect.h - size) / 2f32,
+    );
+    let shackle = Mesh::new_line(
+        ctx,
+        &[
+            [x + size * 0.25f32, y + size * 0.5f32],
+            [x + size * 0.25f32, y + size * 0.1f32],
+            [x + size * 0.75f32, y + size * 0.1f32],
+            [x + size * 0.75f32, y + size * 0.5f32],
+        ],
+        (size * 0.12f32).max(1f32),
+        color,
+    )?;
+    graphics::draw(ctx, &shackle, DrawParam::new())?;
+    let body = Mesh::new_rectangle(
+        ctx,
+        DrawMode::fill(),
+        Rect::new(x, y + size * 0.45f32, size, size * 0.55f32),
+        color,
+    )?;
+    graphics::draw(ctx, &body, DrawParam::new())
+}
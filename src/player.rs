@@ -1,80 +1,60 @@
 use ggez::graphics::{self, Color, DrawParam, Image, Rect};
-use ggez::timer::delta;
 use ggez::{Context, GameResult};
 
-const WALK_TIME: f32 = 0.34f32;
+use ld47_core::player::{Player as PlayerBody, PlayerState};
 
-#[derive(PartialEq)]
-pub enum PlayerState {
-    Standing,
-    Walking(bool, f32),
-}
+use crate::assets::Assets;
+
+// jumps larger than this between steps are teleports and are not interpolated
+const MAX_INTERPOLATION_DISTANCE: f32 = 32f32;
+// the sprite has three frames side by side
+const FRAME_WIDTH: f32 = 1f32 / 3f32;
 
 pub struct Player {
     sprite: Image,
-    pub x: f32,
-    pub y: f32,
-    pub rot: f32,
-    state: PlayerState,
     pub color: Color,
-    xflip: bool,
 }
 
 impl Player {
-    pub fn new(ctx: &mut Context, color: Color) -> GameResult<Self> {
+    pub fn new(ctx: &mut Context, assets: &Assets, color: Color) -> GameResult<Self> {
         Ok(Self {
-            sprite: Image::new(ctx, "/player_sprite.png")?,
-            x: 300f32,
-            y: 300f32,
-            rot: 0f32,
-            state: PlayerState::Standing,
+            sprite: assets.get_image(ctx, "player")?,
             color,
-            xflip: false,
         })
     }
 
-    pub fn set_walking(&mut self, is_walking: bool) {
-        if is_walking {
-            if self.state == PlayerState::Standing {
-                self.state = PlayerState::Walking(true, 0f32);
-            }
-        } else {
-            self.state = PlayerState::Standing;
-        }
-    }
-
-    pub fn set_xflip(&mut self, xflip: bool) {
-        self.xflip = xflip;
-    }
-
-    pub fn update(&mut self, ctx: &mut Context) -> GameResult<()> {
-        let dt = delta(ctx);
-        match &mut self.state {
-            PlayerState::Standing => (),
-            PlayerState::Walking(ref mut left, ref mut timer) => {
-                *timer += dt.as_secs_f32();
-                if *timer >= WALK_TIME {
-                    *timer -= WALK_TIME;
-                    *left = !*left;
-                }
-                //println!("{}", timer);
-            }
-        }
+    pub fn reload(&mut self, ctx: &mut Context, assets: &Assets) -> GameResult<()> {
+        self.sprite = assets.get_image(ctx, "player")?;
         Ok(())
     }
 
-    pub fn draw(&mut self, ctx: &mut Context) -> GameResult<()> {
-        match &self.state {
+    pub fn draw(&self, ctx: &mut Context, body: &PlayerBody, alpha: f32) -> GameResult<()> {
+        let (prev_x, prev_y, prev_rot) = body.get_previous();
+        let (x, y, rot) = if (body.x - prev_x).abs() > MAX_INTERPOLATION_DISTANCE
+            || (body.y - prev_y).abs() > MAX_INTERPOLATION_DISTANCE
+        {
+            (body.x, body.y, body.rot)
+        } else {
+            (
+                prev_x + (body.x - prev_x) * alpha,
+                prev_y + (body.y - prev_y) * alpha,
+                prev_rot + (body.rot - prev_rot) * alpha,
+            )
+        };
+        let color = Color::new(self.color.r, self.color.g, self.color.b, body.opacity);
+        let state = body.get_state();
+        let xflip = body.get_xflip();
+        match &state {
             PlayerState::Standing => {
-                if self.xflip {
+                if xflip {
                     graphics::draw(
                         ctx,
                         &self.sprite,
                         DrawParam::new()
-                            .src(Rect::new(0f32, 0f32, 0.3333333333333f32, 1f32))
-                            .dest([self.x, self.y])
-                            .rotation(self.rot)
-                            .color(self.color)
+                            .src(Rect::new(0f32, 0f32, FRAME_WIDTH, 1f32))
+                            .dest([x, y])
+                            .rotation(rot)
+                            .color(color)
                             .scale([-1f32, 1f32])
                             .offset([1f32, 0f32]),
                     )?;
@@ -83,30 +63,25 @@ impl Player {
                         ctx,
                         &self.sprite,
                         DrawParam::new()
-                            .src(Rect::new(0f32, 0f32, 0.3333333333333f32, 1f32))
-                            .dest([self.x, self.y])
-                            .rotation(self.rot)
-                            .color(self.color),
+                            .src(Rect::new(0f32, 0f32, FRAME_WIDTH, 1f32))
+                            .dest([x, y])
+                            .rotation(rot)
+                            .color(color),
                     )?;
                 }
             }
             PlayerState::Walking(step, _) => {
                 if *step {
                     //print!("step 0, ");
-                    if self.xflip {
+                    if xflip {
                         graphics::draw(
                             ctx,
                             &self.sprite,
                             DrawParam::new()
-                                .src(Rect::new(
-                                    0.3333333333333f32,
-                                    0f32,
-                                    0.3333333333333f32,
-                                    1f32,
-                                ))
-                                .dest([self.x, self.y])
-                                .rotation(self.rot)
-                                .color(self.color)
+                                .src(Rect::new(FRAME_WIDTH, 0f32, FRAME_WIDTH, 1f32))
+                                .dest([x, y])
+                                .rotation(rot)
+                                .color(color)
                                 .scale([-1f32, 1f32])
                                 .offset([1f32, 0f32]),
                         )?;
@@ -116,34 +91,24 @@ impl Player {
                             ctx,
                             &self.sprite,
                             DrawParam::new()
-                                .src(Rect::new(
-                                    0.3333333333333f32,
-                                    0f32,
-                                    0.3333333333333f32,
-                                    1f32,
-                                ))
-                                .dest([self.x, self.y])
-                                .rotation(self.rot)
-                                .color(self.color),
+                                .src(Rect::new(FRAME_WIDTH, 0f32, FRAME_WIDTH, 1f32))
+                                .dest([x, y])
+                                .rotation(rot)
+                                .color(color),
                         )?;
                         //println!("right");
                     }
                 } else {
                     //print!("step 1, ");
-                    if self.xflip {
+                    if xflip {
                         graphics::draw(
                             ctx,
                             &self.sprite,
                             DrawParam::new()
-                                .src(Rect::new(
-                                    0.6666666666666f32,
-                                    0f32,
-                                    0.3333333333333f32,
-                                    1f32,
-                                ))
-                                .dest([self.x, self.y])
-                                .rotation(self.rot)
-                                .color(self.color)
+                                .src(Rect::new(FRAME_WIDTH * 2f32, 0f32, FRAME_WIDTH, 1f32))
+                                .dest([x, y])
+                                .rotation(rot)
+                                .color(color)
                                 .scale([-1f32, 1f32])
                                 .offset([1f32, 0f32]),
                         )?;
@@ -153,15 +118,10 @@ impl Player {
                             ctx,
                             &self.sprite,
                             DrawParam::new()
-                                .src(Rect::new(
-                                    0.6666666666666f32,
-                                    0f32,
-                                    0.3333333333333f32,
-                                    1f32,
-                                ))
-                                .dest([self.x, self.y])
-                                .rotation(self.rot)
-                                .color(self.color),
+                                .src(Rect::new(FRAME_WIDTH * 2f32, 0f32, FRAME_WIDTH, 1f32))
+                                .dest([x, y])
+                                .rotation(rot)
+                                .color(color),
                         )?;
                         //println!("right");
                     }
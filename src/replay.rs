@@ -0,0 +1,234 @@
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+
+use ggez::input::keyboard::KeyCode;
+use ggez::input::mouse::MouseButton;
+
+use ld47_core::world::{Difficulty, Options, Room};
+
+use crate::input::{self, Action};
+use crate::settings;
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum ReplayEvent {
+    Step,
+    // given to the scene before it is turned into an action, like when a key
+    // is being bound
+    KeyDown(KeyCode),
+    ActionDown(Action),
+    ActionUp(Action),
+    MouseDown(MouseButton, f32, f32),
+    MouseUp(MouseButton, f32, f32),
+    // what a new game starts out with from here on, first in every replay
+    Options(Options),
+    // the language and the ids of the enabled mods, in the order they are
+    // applied, which are only at the start of a replay
+    Language(String),
+    Mods(Vec<String>),
+}
+
+impl ReplayEvent {
+    pub fn to_line(&self) -> String {
+        match self {
+            ReplayEvent::Step => String::from("step"),
+            ReplayEvent::KeyDown(keycode) => match input::get_key_name(*keycode) {
+                Some(name) => format!("key_down {}", name),
+                None => format!("key_down code {}", input::get_key_code(*keycode)),
+            },
+            ReplayEvent::ActionDown(action) => format!("action_down {}", action.get_name()),
+            ReplayEvent::ActionUp(action) => format!("action_up {}", action.get_name()),
+            ReplayEvent::MouseDown(button, x, y) => {
+                format!("mouse_down {} {} {}", mouse_button_name(*button), x, y)
+            }
+            ReplayEvent::MouseUp(button, x, y) => {
+                format!("mouse_up {} {} {}", mouse_button_name(*button), x, y)
+            }
+            ReplayEvent::Options(options) => format!(
+                "options difficulty {} seed {} start_room {} skip_intro {} text_speed {}",
+                options.difficulty.get_name(),
+                options
+                    .seed
+                    .map_or_else(|| String::from("none"), |seed| seed.to_string()),
+                options.start_room.map_or("none", |room| room.get_name()),
+                if options.skip_intro { "on" } else { "off" },
+                settings::get_text_speed_name(options.text_speed)
+            ),
+            ReplayEvent::Language(language) => format!("language {}", language),
+            ReplayEvent::Mods(ids) => {
+                let mut line = String::from("mods");
+                for id in ids {
+                    line.push(' ');
+                    line.push_str(id);
+                }
+                line
+            }
+        }
+    }
+
+    pub fn from_line(line: &str) -> Option<ReplayEvent> {
+        let mut words = line.split_whitespace();
+        match words.next()? {
+            "step" => Some(ReplayEvent::Step),
+            "key_down" => match words.next()? {
+                "code" => Some(ReplayEvent::KeyDown(input::key_from_code(
+                    words.next()?.parse().ok()?,
+                )?)),
+                name => Some(ReplayEvent::KeyDown(input::key_from_name(name)?)),
+            },
+            "action_down" => Some(ReplayEvent::ActionDown(Action::from_name(words.next()?)?)),
+            "action_up" => Some(ReplayEvent::ActionUp(Action::from_name(words.next()?)?)),
+            "mouse_down" => Some(ReplayEvent::MouseDown(
+                mouse_button_from_name(words.next()?)?,
+                words.next()?.parse().ok()?,
+                words.next()?.parse().ok()?,
+            )),
+            "mouse_up" => Some(ReplayEvent::MouseUp(
+                mouse_button_from_name(words.next()?)?,
+                words.next()?.parse().ok()?,
+                words.next()?.parse().ok()?,
+            )),
+            "options" => {
+                // each value comes after its name, in the order they are written
+                let mut value = |name: &str| match (words.next(), words.next()) {
+                    (Some(given), Some(value)) if given == name => Some(value),
+                    _ => None,
+                };
+                let difficulty = Difficulty::from_name(value("difficulty")?)?;
+                let seed = match value("seed")? {
+                    "none" => None,
+                    seed => Some(seed.parse().ok()?),
+                };
+                let start_room = match value("start_room")? {
+                    "none" => None,
+                    room => Some(Room::from_name(room)?),
+                };
+                let skip_intro = match value("skip_intro")? {
+                    "on" => true,
+                    "off" => false,
+                    _ => return None,
+                };
+                let text_speed = settings::parse_text_speed(value("text_speed")?)?;
+                Some(ReplayEvent::Options(Options {
+                    difficulty,
+                    seed,
+                    start_room,
+                    skip_intro,
+                    text_speed,
+                }))
+            }
+            "language" => Some(ReplayEvent::Language(words.next()?.to_string())),
+            "mods" => Some(ReplayEvent::Mods(words.map(str::to_string).collect())),
+            _ => None,
+        }
+    }
+}
+
+fn mouse_button_name(button: MouseButton) -> String {
+    match button {
+        MouseButton::Left => String::from("Left"),
+        MouseButton::Right => String::from("Right"),
+        MouseButton::Middle => String::from("Middle"),
+        MouseButton::Other(id) => format!("Other{}", id),
+    }
+}
+
+fn mouse_button_from_name(name: &str) -> Option<MouseButton> {
+    match name {
+        "Left" => Some(MouseButton::Left),
+        "Right" => Some(MouseButton::Right),
+        "Middle" => Some(MouseButton::Middle),
+        other => Some(MouseButton::Other(
+            other.strip_prefix("Other")?.parse().ok()?,
+        )),
+    }
+}
+
+pub struct Recorder {
+    writer: BufWriter<File>,
+}
+
+impl Recorder {
+    pub fn new<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        Ok(Self {
+            writer: BufWriter::new(File::create(path)?),
+        })
+    }
+
+    pub fn record(&mut self, event: &ReplayEvent) -> io::Result<()> {
+        writeln!(self.writer, "{}", event.to_line())
+    }
+}
+
+pub struct Replay {
+    events: VecDeque<ReplayEvent>,
+    // what the game was started with when it was recorded
+    options: Option<Options>,
+    language: Option<String>,
+    mods: Option<Vec<String>>,
+}
+
+impl Replay {
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let reader = BufReader::new(File::open(path)?);
+        let mut events = VecDeque::new();
+        for (idx, line) in reader.lines().enumerate() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            match ReplayEvent::from_line(&line) {
+                Some(event) => events.push_back(event),
+                None => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("Invalid replay event on line {}: \"{}\"", idx + 1, line),
+                    ))
+                }
+            }
+        }
+        let mut replay = Self {
+            events: VecDeque::new(),
+            options: None,
+            language: None,
+            mods: None,
+        };
+        loop {
+            match events.front() {
+                Some(ReplayEvent::Options(options)) => replay.options = Some(*options),
+                Some(ReplayEvent::Language(language)) => replay.language = Some(language.clone()),
+                Some(ReplayEvent::Mods(ids)) => replay.mods = Some(ids.clone()),
+                _ => break,
+            }
+            events.pop_front();
+        }
+        replay.events = events;
+        Ok(replay)
+    }
+
+    pub fn get_options(&self) -> Option<Options> {
+        self.options
+    }
+
+    pub fn get_language(&self) -> Option<&str> {
+        self.language.as_deref()
+    }
+
+    pub fn get_mods(&self) -> Option<&[String]> {
+        self.mods.as_deref()
+    }
+
+    // Returns the input events received before the next recorded step, or
+    // None once the replay is exhausted.
+    pub fn next_step(&mut self) -> Option<Vec<ReplayEvent>> {
+        let mut inputs = Vec::new();
+        while let Some(event) = self.events.pop_front() {
+            match event {
+                ReplayEvent::Step => return Some(inputs),
+                e => inputs.push(e),
+            }
+        }
+        None
+    }
+}
@@ -0,0 +1,161 @@
+use std::env;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use ggez::filesystem;
+use ggez::{Context, GameError};
+
+pub const RESOURCES_ENV_VAR: &str = "LD47_RESOURCES";
+
+// Every resource that failed to load, so they can all be reported at once
+// instead of panicking on the first one
+#[derive(Debug)]
+pub struct ResourceError {
+    failed: Vec<(String, String)>,
+    expected: usize,
+    search_paths: Vec<PathBuf>,
+}
+
+impl ResourceError {
+    pub fn new(
+        ctx: &Context,
+        failed: Vec<(String, String)>,
+        expected: usize,
+        resource_dirs: &[ResourceDir],
+    ) -> Self {
+        Self {
+            failed,
+            expected,
+            search_paths: get_search_paths(ctx, resource_dirs),
+        }
+    }
+}
+
+impl fmt::Display for ResourceError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(
+            f,
+            "Failed to load {} of {} resources:",
+            self.failed.len(),
+            self.expected
+        )?;
+        for (name, reason) in &self.failed {
+            writeln!(f, "    {} ({})", name, reason)?;
+        }
+        writeln!(f, "Searched for resources in:")?;
+        for path in &self.search_paths {
+            writeln!(f, "    {}", path.display())?;
+        }
+        write!(
+            f,
+            "The \"resources\" directory needs to be next to the executable, see the README"
+        )
+    }
+}
+
+impl std::error::Error for ResourceError {}
+
+impl From<ResourceError> for GameError {
+    fn from(e: ResourceError) -> GameError {
+        GameError::ResourceLoadError(e.to_string())
+    }
+}
+
+// The places ggez looks in for resources, in the order it checks them
+pub fn get_search_paths(ctx: &Context, resource_dirs: &[ResourceDir]) -> Vec<PathBuf> {
+    let resources_dir = filesystem::resources_dir(ctx);
+    let mut resources_zip = resources_dir.to_path_buf();
+    resources_zip.set_extension("zip");
+    let mut paths = vec![
+        resources_dir.to_path_buf(),
+        resources_zip,
+        filesystem::user_data_dir(ctx).to_path_buf(),
+        filesystem::user_config_dir(ctx).to_path_buf(),
+    ];
+    for dir in resource_dirs {
+        paths.push(dir.path.clone());
+    }
+    paths
+}
+
+// where ggez looks first, the "resources" directory next to the executable
+pub fn get_exe_resources_dir() -> Option<PathBuf> {
+    env::current_exe()
+        .ok()
+        .and_then(|exe| exe.parent().map(|dir| dir.join("resources")))
+}
+
+pub struct ResourceDir {
+    pub source: &'static str,
+    pub path: PathBuf,
+}
+
+// Directories that may hold the resources besides the one next to the
+// executable, in the order they are added to the context. The one given on the
+// command line comes first, then the environment variable, then the one in the
+// source tree so that `cargo run` works.
+pub fn find_resource_dirs(arg_path: Option<&Path>) -> Vec<ResourceDir> {
+    let mut dirs = Vec::new();
+    if let Some(path) = arg_path {
+        dirs.push(ResourceDir {
+            source: "--resources",
+            path: path.to_path_buf(),
+        });
+    }
+    if let Some(path) = env::var_os(RESOURCES_ENV_VAR) {
+        dirs.push(ResourceDir {
+            source: RESOURCES_ENV_VAR,
+            path: PathBuf::from(path),
+        });
+    }
+    if let Some(path) = env::var_os("CARGO_MANIFEST_DIR") {
+        let mut path = PathBuf::from(path);
+        path.push("resources");
+        dirs.push(ResourceDir {
+            source: "CARGO_MANIFEST_DIR",
+            path,
+        });
+    }
+
+    for dir in &dirs {
+        if !dir.path.is_dir() {
+            println!(
+                "WARNING: Resources directory \"{}\" from {} does not exist",
+                dir.path.display(),
+                dir.source
+            );
+        }
+    }
+    dirs.retain(|dir| dir.path.is_dir());
+    dirs
+}
+
+// ggez always checks the directory next to the executable first, so that one
+// wins over everything that was added
+pub fn log_resource_dir(ctx: &Context, resource_dirs: &[ResourceDir]) {
+    let default_dir = filesystem::resources_dir(ctx);
+    if default_dir.is_dir() {
+        println!(
+            "Using resources from \"{}\" (executable directory)",
+            default_dir.display()
+        );
+        for dir in resource_dirs {
+            if dir.source != "CARGO_MANIFEST_DIR" {
+                println!(
+                    "WARNING: \"{}\" from {} is only used for files missing from \"{}\"",
+                    dir.path.display(),
+                    dir.source,
+                    default_dir.display()
+                );
+            }
+        }
+    } else if let Some(dir) = resource_dirs.first() {
+        println!(
+            "Using resources from \"{}\" ({})",
+            dir.path.display(),
+            dir.source
+        );
+    } else {
+        println!("WARNING: No resources directory found");
+    }
+}
@@ -0,0 +1,43 @@
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+use ggez::{filesystem, Context};
+
+use ld47_core::save::Save;
+
+// the flags and conversations kept between runs, in the user config directory
+const SAVE_FILE: &str = "save.txt";
+
+fn get_save_path(ctx: &Context) -> PathBuf {
+    filesystem::user_config_dir(ctx).join(SAVE_FILE)
+}
+
+// Nothing is kept from a save that can't be read, so the game starts over
+pub fn load(ctx: &Context) -> Save {
+    let contents = match fs::read_to_string(get_save_path(ctx)) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Save::default(),
+        Err(e) => {
+            println!("WARNING: Failed to read the save: {}", e);
+            return Save::default();
+        }
+    };
+    match Save::parse(&contents) {
+        Ok(save) => save,
+        Err(errors) => {
+            for error in errors {
+                println!("WARNING: {} {}", SAVE_FILE, error);
+            }
+            Save::default()
+        }
+    }
+}
+
+pub fn write(ctx: &Context, save: &Save) -> io::Result<()> {
+    let path = get_save_path(ctx);
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(path, save.to_text())
+}
@@ -1,64 +1,174 @@
 use std::cell::RefCell;
 use std::rc::Rc;
 
-use ggez::event::EventHandler;
-use ggez::graphics::{
-    self, Color, DrawMode, DrawParam, Font, Mesh, Rect, Scale, Text, TextFragment,
-};
+use ggez::graphics::{self, Color, DrawMode, DrawParam, Mesh, Rect, Text};
 use ggez::input::mouse::MouseButton;
 use ggez::{Context, GameResult};
 
 use super::Scene;
+use crate::assets::Assets;
+use crate::fonts::FontStack;
+use crate::game::GameState;
+use crate::input::Action;
 use crate::player::Player;
+use crate::settings::Settings;
+
+const MODS_BUTTON: [f32; 4] = [20f32, 540f32, 100f32, 40f32];
+const SETTINGS_BUTTON: [f32; 4] = [140f32, 540f32, 140f32, 40f32];
+const SWATCH_SIZE: f32 = 128f32;
+// the top left of each color, in the order of GameStartScene::colors
+const SWATCHES: [[f32; 2]; 7] = [
+    [400f32 - 256f32, 200f32],
+    [400f32 - 128f32, 200f32],
+    [400f32, 200f32],
+    [400f32 + 128f32, 200f32],
+    [400f32 - 192f32, 328f32],
+    [400f32 - 64f32, 328f32],
+    [400f32 + 64f32, 328f32],
+];
+// the cursor goes over the colors and then the two buttons
+const MODS_ITEM: usize = SWATCHES.len();
+const SETTINGS_ITEM: usize = SWATCHES.len() + 1;
 
 pub struct GameStartScene {
-    font: Font,
+    fonts: FontStack,
     finished: bool,
-    color_pale: Color,
-    color_white: Color,
-    color_brown: Color,
-    color_black: Color,
-    color_red: Color,
-    color_green: Color,
-    color_blue: Color,
+    colors: [Color; 7],
+    // what is picked with the keyboard or a gamepad
+    cursor: usize,
     pick_color_text: Text,
+    mods_text: Text,
+    settings_text: Text,
+    // a menu opened from here instead of starting the game
+    open_menu: Option<GameState>,
     player: Rc<RefCell<Player>>,
+    // shown in place of pick_color_text once a color is picked
+    loading: String,
     drawed_loading_text: bool,
 }
 
 impl GameStartScene {
-    pub fn new(_ctx: &mut Context, font: Font, player: Rc<RefCell<Player>>) -> Self {
-        let mut pick_color_text: Text = Text::new(
-            TextFragment::new("Pick your color").color(Color::from_rgb(0xff, 0xff, 0xff)),
-        );
-        pick_color_text.set_font(font, Scale::uniform(32f32));
-        Self {
-            font,
+    pub fn new(
+        ctx: &mut Context,
+        assets: &Assets,
+        player: Rc<RefCell<Player>>,
+        settings: &Settings,
+    ) -> GameResult<Self> {
+        let strings = assets.get_strings(ctx, &settings.language)?;
+        let fonts = FontStack::new(ctx, assets, &strings)?;
+        let pick_color_text = fonts.text(strings.get("menu.pick_color"), 32f32);
+        let mods_text = fonts.text(strings.get("menu.mods"), 26f32);
+        let settings_text = fonts.text(strings.get("menu.settings"), 26f32);
+        Ok(Self {
+            fonts,
             finished: false,
-            color_pale: Color::from_rgb(0xfd, 0xd9, 0xbd),
-            color_white: Color::from_rgb(0xfd, 0xba, 0x85),
-            color_brown: Color::from_rgb(0xb5, 0x72, 0x37),
-            color_black: Color::from_rgb(0x4b, 0x28, 0x0a),
-            color_red: Color::from_rgb(0xff, 0, 0),
-            color_green: Color::from_rgb(0, 0xff, 0),
-            color_blue: Color::from_rgb(0, 0, 0xff),
+            colors: [
+                Color::from_rgb(0xfd, 0xd9, 0xbd),
+                Color::from_rgb(0xfd, 0xba, 0x85),
+                Color::from_rgb(0xb5, 0x72, 0x37),
+                Color::from_rgb(0x4b, 0x28, 0x0a),
+                Color::from_rgb(0xff, 0, 0),
+                Color::from_rgb(0, 0xff, 0),
+                Color::from_rgb(0, 0, 0xff),
+            ],
+            cursor: 0,
             pick_color_text,
+            mods_text,
+            settings_text,
+            open_menu: None,
             player,
+            loading: strings.get("menu.loading").to_string(),
             drawed_loading_text: false,
+        })
+    }
+
+    pub fn new_boxed(
+        ctx: &mut Context,
+        assets: &Assets,
+        player: Rc<RefCell<Player>>,
+        settings: &Settings,
+    ) -> GameResult<Box<Self>> {
+        Ok(Box::new(Self::new(ctx, assets, player, settings)?))
+    }
+
+    fn pick(&mut self, item: usize) {
+        match item {
+            MODS_ITEM => self.open_menu = Some(GameState::Mods),
+            SETTINGS_ITEM => self.open_menu = Some(GameState::Settings),
+            _ => {
+                if let Some(color) = self.colors.get(item) {
+                    self.player.borrow_mut().color = *color;
+                    self.finished = true;
+                }
+            }
+        }
+    }
+
+    // the closest item from the cursor in the direction, favouring those in
+    // line with it
+    fn move_cursor(&mut self, dx: f32, dy: f32) {
+        let center = |item: usize| {
+            let rect = get_item_rect(item);
+            (rect[0] + rect[2] / 2f32, rect[1] + rect[3] / 2f32)
+        };
+        let (x, y) = center(self.cursor);
+        let mut best: Option<(f32, usize)> = None;
+        for item in 0..=SETTINGS_ITEM {
+            let (item_x, item_y) = center(item);
+            let along = (item_x - x) * dx + (item_y - y) * dy;
+            let across = ((item_x - x) * dy - (item_y - y) * dx).abs();
+            if along <= 0f32 {
+                continue;
+            }
+            let distance = along + across * 2f32;
+            match best {
+                Some((best_distance, _)) if best_distance <= distance => (),
+                _ => best = Some((distance, item)),
+            }
+        }
+        if let Some((_, item)) = best {
+            self.cursor = item;
         }
     }
+}
 
-    pub fn new_boxed(ctx: &mut Context, font: Font, player: Rc<RefCell<Player>>) -> Box<Self> {
-        Box::new(Self::new(ctx, font, player))
+fn get_item_rect(item: usize) -> [f32; 4] {
+    match item {
+        MODS_ITEM => MODS_BUTTON,
+        SETTINGS_ITEM => SETTINGS_BUTTON,
+        _ => {
+            let [x, y] = SWATCHES[item.min(SWATCHES.len() - 1)];
+            [x, y, SWATCH_SIZE, SWATCH_SIZE]
+        }
     }
 }
 
-impl EventHandler for GameStartScene {
-    fn update(&mut self, _ctx: &mut Context) -> GameResult<()> {
+fn is_in_button(button: [f32; 4], x: f32, y: f32) -> bool {
+    x > button[0] && x < button[0] + button[2] && y > button[1] && y < button[1] + button[3]
+}
+
+fn draw_button(ctx: &mut Context, button: [f32; 4], text: &Text) -> GameResult<()> {
+    let mesh = Mesh::new_rectangle(
+        ctx,
+        DrawMode::stroke(2f32),
+        Rect::new(button[0], button[1], button[2], button[3]),
+        Color::from_rgb(0xff, 0xff, 0xff),
+    )?;
+    graphics::draw(ctx, &mesh, DrawParam::new())?;
+    let text_width = text.width(ctx) as f32 / 2f32;
+    graphics::draw(
+        ctx,
+        text,
+        DrawParam::new().dest([button[0] + button[2] / 2f32 - text_width, button[1] + 6f32]),
+    )
+}
+
+impl Scene for GameStartScene {
+    fn update(&mut self, _ctx: &mut Context, _dt: f32) -> GameResult<()> {
         Ok(())
     }
 
-    fn draw(&mut self, ctx: &mut Context) -> GameResult<()> {
+    fn draw(&mut self, ctx: &mut Context, _alpha: f32) -> GameResult<()> {
         let text_width = self.pick_color_text.width(ctx) as f32 / 2f32;
         graphics::draw(
             ctx,
@@ -66,90 +176,35 @@ impl EventHandler for GameStartScene {
             DrawParam::new().dest([400f32 - text_width, 150f32]),
         )?;
 
-        let pale_mesh = Mesh::new_rectangle(
-            ctx,
-            DrawMode::fill(),
-            Rect::new(0f32, 0f32, 128f32, 128f32),
-            self.color_pale,
-        )?;
-        graphics::draw(
-            ctx,
-            &pale_mesh,
-            DrawParam::new().dest([400f32 - 128f32 - 64f32 - 64f32, 200f32]),
-        )?;
-
-        let white_mesh = Mesh::new_rectangle(
-            ctx,
-            DrawMode::fill(),
-            Rect::new(0f32, 0f32, 128f32, 128f32),
-            self.color_white,
-        )?;
-        graphics::draw(
-            ctx,
-            &white_mesh,
-            DrawParam::new().dest([400f32 - 64f32 - 64f32, 200f32]),
-        )?;
-
-        let brown_mesh = Mesh::new_rectangle(
-            ctx,
-            DrawMode::fill(),
-            Rect::new(0f32, 0f32, 128f32, 128f32),
-            self.color_brown,
-        )?;
-        graphics::draw(ctx, &brown_mesh, DrawParam::new().dest([400f32, 200f32]))?;
-
-        let black_mesh = Mesh::new_rectangle(
-            ctx,
-            DrawMode::fill(),
-            Rect::new(0f32, 0f32, 128f32, 128f32),
-            self.color_black,
-        )?;
-        graphics::draw(
-            ctx,
-            &black_mesh,
-            DrawParam::new().dest([400f32 + 128f32, 200f32]),
-        )?;
-
-        let red_mesh = Mesh::new_rectangle(
-            ctx,
-            DrawMode::fill(),
-            Rect::new(0f32, 0f32, 128f32, 128f32),
-            self.color_red,
-        )?;
-        graphics::draw(
-            ctx,
-            &red_mesh,
-            DrawParam::new().dest([400f32 - 128f32 - 64f32, 328f32]),
-        )?;
+        for (swatch, color) in SWATCHES.iter().zip(self.colors.iter()) {
+            let mesh = Mesh::new_rectangle(
+                ctx,
+                DrawMode::fill(),
+                Rect::new(0f32, 0f32, SWATCH_SIZE, SWATCH_SIZE),
+                *color,
+            )?;
+            graphics::draw(ctx, &mesh, DrawParam::new().dest(*swatch))?;
+        }
 
-        let green_mesh = Mesh::new_rectangle(
-            ctx,
-            DrawMode::fill(),
-            Rect::new(0f32, 0f32, 128f32, 128f32),
-            self.color_green,
-        )?;
-        graphics::draw(
-            ctx,
-            &green_mesh,
-            DrawParam::new().dest([400f32 - 64f32, 328f32]),
-        )?;
+        draw_button(ctx, MODS_BUTTON, &self.mods_text)?;
+        draw_button(ctx, SETTINGS_BUTTON, &self.settings_text)?;
 
-        let blue_mesh = Mesh::new_rectangle(
+        let rect = get_item_rect(self.cursor);
+        let cursor_mesh = Mesh::new_rectangle(
             ctx,
-            DrawMode::fill(),
-            Rect::new(0f32, 0f32, 128f32, 128f32),
-            self.color_blue,
-        )?;
-        graphics::draw(
-            ctx,
-            &blue_mesh,
-            DrawParam::new().dest([400f32 + 128f32 - 64f32, 328f32]),
+            DrawMode::stroke(4f32),
+            Rect::new(
+                rect[0] - 4f32,
+                rect[1] - 4f32,
+                rect[2] + 8f32,
+                rect[3] + 8f32,
+            ),
+            Color::from_rgb(0xff, 0xff, 0xff),
         )?;
+        graphics::draw(ctx, &cursor_mesh, DrawParam::new())?;
 
         if self.finished {
-            self.pick_color_text = Text::new("Loading...");
-            self.pick_color_text
-                .set_font(self.font, Scale::uniform(32f32));
+            self.pick_color_text = self.fonts.text(&self.loading, 32f32);
             let text_width = self.pick_color_text.width(ctx) as f32 / 2f32;
             graphics::draw(
                 ctx,
@@ -169,37 +224,36 @@ impl EventHandler for GameStartScene {
         x: f32,
         y: f32,
     ) {
-        if y > 200f32 && y < 200f32 + 128f32 {
-            if x > 400f32 - 256f32 && x < 400f32 - 128f32 {
-                self.player.borrow_mut().color = self.color_pale;
-                self.finished = true;
-            } else if x > 400f32 - 128f32 && x < 400f32 {
-                self.player.borrow_mut().color = self.color_white;
-                self.finished = true;
-            } else if x > 400f32 && x < 400f32 + 128f32 {
-                self.player.borrow_mut().color = self.color_brown;
-                self.finished = true;
-            } else if x > 400f32 + 128f32 && x < 400f32 + 256f32 {
-                self.player.borrow_mut().color = self.color_black;
-                self.finished = true;
-            }
-        } else if y > 328f32 && y < 328f32 + 128f32 {
-            if x > 400f32 - 128f32 - 64f32 && x < 400f32 - 64f32 {
-                self.player.borrow_mut().color = self.color_red;
-                self.finished = true;
-            } else if x > 400f32 - 64f32 && x < 400f32 + 64f32 {
-                self.player.borrow_mut().color = self.color_green;
-                self.finished = true;
-            } else if x > 400f32 + 64f32 && x < 400f32 + 128f32 + 64f32 {
-                self.player.borrow_mut().color = self.color_blue;
-                self.finished = true;
-            }
+        if self.finished {
+            return;
+        }
+        if let Some(item) =
+            (0..=SETTINGS_ITEM).find(|item| is_in_button(get_item_rect(*item), x, y))
+        {
+            self.cursor = item;
+            self.pick(item);
+        }
+    }
+
+    fn action_down_event(&mut self, _ctx: &mut Context, action: Action) {
+        if self.finished {
+            return;
+        }
+        match action {
+            Action::Left => self.move_cursor(-1f32, 0f32),
+            Action::Right => self.move_cursor(1f32, 0f32),
+            Action::Up => self.move_cursor(0f32, -1f32),
+            Action::Down => self.move_cursor(0f32, 1f32),
+            Action::Interact => self.pick(self.cursor),
+            Action::EnterDoor | Action::Back => (),
         }
     }
-}
 
-impl Scene for GameStartScene {
     fn finished(&self) -> bool {
-        self.finished && self.drawed_loading_text
+        self.open_menu.is_some() || (self.finished && self.drawed_loading_text)
+    }
+
+    fn get_next_state(&self) -> Option<GameState> {
+        self.open_menu
     }
 }
@@ -1,8 +1,64 @@
 pub mod gamestart;
 pub mod mainscene;
+pub mod mods;
+pub mod settings;
 
-use ggez::event::EventHandler;
+use ggez::input::keyboard::KeyCode;
+use ggez::input::mouse::MouseButton;
+use ggez::{Context, GameResult};
+
+use crate::assets::{AssetKind, Assets};
+use crate::game::GameState;
+use crate::input::Action;
+
+pub trait Scene {
+    fn update(&mut self, ctx: &mut Context, dt: f32) -> GameResult<()>;
+
+    // alpha is how far rendering is between the last and the next step
+    fn draw(&mut self, ctx: &mut Context, alpha: f32) -> GameResult<()>;
 
-pub trait Scene: EventHandler {
     fn finished(&self) -> bool;
+
+    // where to go once finished, if not the usual next state
+    fn get_next_state(&self) -> Option<GameState> {
+        None
+    }
+
+    // called in dev mode with the assets that changed on disk
+    fn reload(
+        &mut self,
+        _ctx: &mut Context,
+        _assets: &Assets,
+        _changed: &[(AssetKind, String)],
+    ) -> GameResult<()> {
+        Ok(())
+    }
+
+    fn mouse_button_down_event(
+        &mut self,
+        _ctx: &mut Context,
+        _button: MouseButton,
+        _x: f32,
+        _y: f32,
+    ) {
+    }
+
+    fn mouse_button_up_event(
+        &mut self,
+        _ctx: &mut Context,
+        _button: MouseButton,
+        _x: f32,
+        _y: f32,
+    ) {
+    }
+
+    // lets a scene see a key before it is turned into an action, returns true
+    // if the scene used it
+    fn key_down_event(&mut self, _ctx: &mut Context, _keycode: KeyCode) -> bool {
+        false
+    }
+
+    fn action_down_event(&mut self, _ctx: &mut Context, _action: Action) {}
+
+    fn action_up_event(&mut self, _ctx: &mut Context, _action: Action) {}
 }
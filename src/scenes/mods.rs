@@ -0,0 +1,215 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use ggez::graphics::{self, Align, Color, DrawMode, DrawParam, Mesh, Rect, Text};
+use ggez::input::mouse::MouseButton;
+use ggez::{Context, GameResult};
+
+use super::Scene;
+use crate::assets::Assets;
+use crate::fonts::FontStack;
+use crate::input::Action;
+use crate::mods::Mod;
+use crate::settings::Settings;
+
+const LIST_X: f32 = 150f32;
+const LIST_Y: f32 = 110f32;
+const ROW_HEIGHT: f32 = 40f32;
+const ROW_WIDTH: f32 = 500f32;
+const BACK_BUTTON: [f32; 4] = [350f32, 520f32, 100f32, 40f32];
+const HELP_WIDTH: f32 = 600f32;
+
+pub struct ModsScene {
+    fonts: FontStack,
+    finished: bool,
+    mods: Rc<RefCell<Vec<Mod>>>,
+    cursor: usize,
+    // the names of mods that are on and off
+    on_off: (String, String),
+    title_text: Text,
+    help_text: Text,
+    back_text: Text,
+}
+
+impl ModsScene {
+    pub fn new(
+        ctx: &mut Context,
+        assets: &Assets,
+        mods: Rc<RefCell<Vec<Mod>>>,
+        settings: &Settings,
+    ) -> GameResult<Self> {
+        let strings = assets.get_strings(ctx, &settings.language)?;
+        let fonts = FontStack::new(ctx, assets, &strings)?;
+        let title_text = fonts.text(strings.get("menu.mods"), 32f32);
+        let help = if mods.borrow().is_empty() {
+            strings.get("mods.none")
+        } else {
+            strings.get("mods.help")
+        };
+        let help_text = fonts.wrapped(help, 18f32, HELP_WIDTH, Align::Left);
+        let back_text = fonts.text(strings.get("menu.back"), 26f32);
+        Ok(Self {
+            fonts,
+            finished: false,
+            mods,
+            cursor: 0,
+            on_off: (
+                strings.get("mods.on").to_string(),
+                strings.get("mods.off").to_string(),
+            ),
+            title_text,
+            help_text,
+            back_text,
+        })
+    }
+
+    pub fn new_boxed(
+        ctx: &mut Context,
+        assets: &Assets,
+        mods: Rc<RefCell<Vec<Mod>>>,
+        settings: &Settings,
+    ) -> GameResult<Box<Self>> {
+        Ok(Box::new(Self::new(ctx, assets, mods, settings)?))
+    }
+
+    fn toggle(&mut self, idx: usize) {
+        if let Some(m) = self.mods.borrow_mut().get_mut(idx) {
+            m.enabled = !m.enabled;
+        }
+    }
+
+    // moves the mod under the cursor up or down the list, taking the cursor along
+    fn move_mod(&mut self, down: bool) {
+        let mut mods = self.mods.borrow_mut();
+        if down && self.cursor + 1 < mods.len() {
+            mods.swap(self.cursor, self.cursor + 1);
+            self.cursor += 1;
+        } else if !down && self.cursor > 0 && self.cursor < mods.len() {
+            mods.swap(self.cursor, self.cursor - 1);
+            self.cursor -= 1;
+        }
+    }
+}
+
+impl Scene for ModsScene {
+    fn update(&mut self, _ctx: &mut Context, _dt: f32) -> GameResult<()> {
+        Ok(())
+    }
+
+    fn draw(&mut self, ctx: &mut Context, _alpha: f32) -> GameResult<()> {
+        let text_width = self.title_text.width(ctx) as f32 / 2f32;
+        graphics::draw(
+            ctx,
+            &self.title_text,
+            DrawParam::new().dest([400f32 - text_width, 40f32]),
+        )?;
+
+        for (idx, m) in self.mods.borrow().iter().enumerate() {
+            let y = LIST_Y + idx as f32 * ROW_HEIGHT;
+            if idx == self.cursor {
+                let cursor_mesh = Mesh::new_rectangle(
+                    ctx,
+                    DrawMode::stroke(2f32),
+                    Rect::new(LIST_X, y, ROW_WIDTH, ROW_HEIGHT - 4f32),
+                    Color::from_rgb(0xff, 0xff, 0xff),
+                )?;
+                graphics::draw(ctx, &cursor_mesh, DrawParam::new())?;
+            }
+            let text = self.fonts.text(
+                &format!(
+                    "[{}] {} {}",
+                    if m.enabled {
+                        &self.on_off.0
+                    } else {
+                        &self.on_off.1
+                    },
+                    m.name,
+                    m.version
+                ),
+                24f32,
+            );
+            let color = if m.enabled {
+                Color::from_rgb(0x80, 0xff, 0x80)
+            } else {
+                Color::from_rgb(0xa0, 0xa0, 0xa0)
+            };
+            graphics::draw(
+                ctx,
+                &text,
+                DrawParam::new()
+                    .dest([LIST_X + 10f32, y + 6f32])
+                    .color(color),
+            )?;
+        }
+
+        graphics::draw(
+            ctx,
+            &self.help_text,
+            DrawParam::new().dest([LIST_X - 50f32, 450f32]),
+        )?;
+
+        let back_mesh = Mesh::new_rectangle(
+            ctx,
+            DrawMode::stroke(2f32),
+            Rect::new(
+                BACK_BUTTON[0],
+                BACK_BUTTON[1],
+                BACK_BUTTON[2],
+                BACK_BUTTON[3],
+            ),
+            Color::from_rgb(0xff, 0xff, 0xff),
+        )?;
+        graphics::draw(ctx, &back_mesh, DrawParam::new())?;
+        let text_width = self.back_text.width(ctx) as f32 / 2f32;
+        graphics::draw(
+            ctx,
+            &self.back_text,
+            DrawParam::new().dest([400f32 - text_width, BACK_BUTTON[1] + 6f32]),
+        )?;
+
+        Ok(())
+    }
+
+    fn finished(&self) -> bool {
+        self.finished
+    }
+
+    fn mouse_button_down_event(&mut self, _ctx: &mut Context, button: MouseButton, x: f32, y: f32) {
+        if button != MouseButton::Left {
+            return;
+        }
+        if x > BACK_BUTTON[0]
+            && x < BACK_BUTTON[0] + BACK_BUTTON[2]
+            && y > BACK_BUTTON[1]
+            && y < BACK_BUTTON[1] + BACK_BUTTON[3]
+        {
+            self.finished = true;
+        } else if x > LIST_X && x < LIST_X + ROW_WIDTH && y > LIST_Y {
+            let idx = ((y - LIST_Y) / ROW_HEIGHT) as usize;
+            if idx < self.mods.borrow().len() {
+                self.cursor = idx;
+                self.toggle(idx);
+            }
+        }
+    }
+
+    fn action_down_event(&mut self, _ctx: &mut Context, action: Action) {
+        match action {
+            Action::Up => {
+                if self.cursor > 0 {
+                    self.cursor -= 1;
+                }
+            }
+            Action::Down => {
+                if self.cursor + 1 < self.mods.borrow().len() {
+                    self.cursor += 1;
+                }
+            }
+            Action::Left => self.move_mod(false),
+            Action::Right => self.move_mod(true),
+            Action::Interact => self.toggle(self.cursor),
+            Action::Back => self.finished = true,
+            Action::EnterDoor => (),
+        }
+    }
+}
@@ -0,0 +1,407 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use ggez::graphics::{self, Align, Color, DrawMode, DrawParam, Mesh, Rect, Text};
+use ggez::input::keyboard::KeyCode;
+use ggez::input::mouse::MouseButton;
+use ggez::{Context, GameResult};
+
+use ld47_core::strings::Strings;
+
+use super::Scene;
+use crate::assets::{Assets, DEFAULT_LANGUAGE};
+use crate::fonts::FontStack;
+use crate::input::{self, Action};
+use crate::settings::{ColorblindMode, Settings, RESOLUTIONS, TEXT_SPEEDS};
+
+const LIST_X: f32 = 150f32;
+const LIST_Y: f32 = 64f32;
+const ROW_HEIGHT: f32 = 25f32;
+const ROW_WIDTH: f32 = 500f32;
+const BACK_BUTTON: [f32; 4] = [350f32, 540f32, 100f32, 40f32];
+const VOLUME_STEPS: f32 = 10f32;
+const HELP_WIDTH: f32 = 760f32;
+
+#[derive(Copy, Clone, PartialEq)]
+enum Row {
+    MasterVolume,
+    MusicVolume,
+    SfxVolume,
+    Fullscreen,
+    Resolution,
+    TextSpeed,
+    Colorblind,
+    Subtitles,
+    Language,
+    Key(Action),
+    ResetKeys,
+}
+
+impl Row {
+    // the string id of the label
+    fn get_label(self) -> &'static str {
+        match self {
+            Row::MasterVolume => "settings.volume",
+            Row::MusicVolume => "settings.music_volume",
+            Row::SfxVolume => "settings.sfx_volume",
+            Row::Fullscreen => "settings.fullscreen",
+            Row::Resolution => "settings.resolution",
+            Row::TextSpeed => "settings.text_speed",
+            Row::Colorblind => "settings.colorblind",
+            Row::Subtitles => "settings.subtitles",
+            Row::Language => "settings.language",
+            Row::Key(Action::Left) => "settings.key.left",
+            Row::Key(Action::Right) => "settings.key.right",
+            Row::Key(Action::Up) => "settings.key.up",
+            Row::Key(Action::Down) => "settings.key.down",
+            Row::Key(Action::Interact) => "settings.key.interact",
+            Row::Key(Action::EnterDoor) => "settings.key.enter_door",
+            Row::Key(Action::Back) => "settings.key.back",
+            Row::ResetKeys => "settings.reset_keys",
+        }
+    }
+}
+
+fn cycle(idx: usize, len: usize, forward: bool) -> usize {
+    if forward {
+        (idx + 1) % len
+    } else {
+        (idx + len - 1) % len
+    }
+}
+
+// volumes go up and down in tenths, wrapping around only when asked to
+fn step_volume(volume: &mut f32, forward: bool, wrap: bool) {
+    let step = (*volume * VOLUME_STEPS).round();
+    let step = match (forward, wrap) {
+        (true, true) if step >= VOLUME_STEPS => 0f32,
+        (false, true) if step <= 0f32 => VOLUME_STEPS,
+        (true, _) => (step + 1f32).min(VOLUME_STEPS),
+        (false, _) => (step - 1f32).max(0f32),
+    };
+    *volume = step / VOLUME_STEPS;
+}
+
+pub struct SettingsScene {
+    finished: bool,
+    settings: Rc<RefCell<Settings>>,
+    rows: Vec<Row>,
+    cursor: usize,
+    // the action whose key is being picked
+    waiting_for_key: Option<Action>,
+    // (name, strings, fonts) of every language, so the menu changes to one as
+    // soon as it is picked
+    languages: Vec<(String, Strings, FontStack)>,
+    english: (Strings, FontStack),
+}
+
+impl SettingsScene {
+    pub fn new(
+        ctx: &mut Context,
+        assets: &Assets,
+        settings: Rc<RefCell<Settings>>,
+    ) -> GameResult<Self> {
+        let mut languages = Vec::new();
+        for name in assets.get_languages() {
+            let strings = assets.get_strings(ctx, name)?;
+            let fonts = FontStack::new(ctx, assets, &strings)?;
+            languages.push((name.to_string(), strings, fonts));
+        }
+        let english = assets.get_strings(ctx, DEFAULT_LANGUAGE)?;
+        let english_fonts = FontStack::new(ctx, assets, &english)?;
+
+        let mut rows = vec![
+            Row::MasterVolume,
+            Row::MusicVolume,
+            Row::SfxVolume,
+            Row::Fullscreen,
+            Row::Resolution,
+            Row::TextSpeed,
+            Row::Colorblind,
+            Row::Subtitles,
+            Row::Language,
+        ];
+        rows.extend(Action::ALL.iter().map(|action| Row::Key(*action)));
+        rows.push(Row::ResetKeys);
+
+        Ok(Self {
+            finished: false,
+            settings,
+            rows,
+            cursor: 0,
+            waiting_for_key: None,
+            languages,
+            english: (english, english_fonts),
+        })
+    }
+
+    pub fn new_boxed(
+        ctx: &mut Context,
+        assets: &Assets,
+        settings: Rc<RefCell<Settings>>,
+    ) -> GameResult<Box<Self>> {
+        Ok(Box::new(Self::new(ctx, assets, settings)?))
+    }
+
+    fn get_language(&self) -> (&Strings, &FontStack) {
+        let settings = self.settings.borrow();
+        self.languages
+            .iter()
+            .find(|(name, _, _)| *name == settings.language)
+            .map(|(_, strings, fonts)| (strings, fonts))
+            .unwrap_or((&self.english.0, &self.english.1))
+    }
+
+    fn get_strings(&self) -> &Strings {
+        self.get_language().0
+    }
+
+    fn get_text(&self, id: &str, size: f32) -> Text {
+        let (strings, fonts) = self.get_language();
+        fonts.text(strings.get(id), size)
+    }
+
+    fn get_value(&self, row: Row) -> String {
+        let settings = self.settings.borrow();
+        let strings = self.get_strings();
+        let on_off = |value: bool| {
+            String::from(strings.get(if value { "settings.on" } else { "settings.off" }))
+        };
+        match row {
+            Row::MasterVolume => format!("{}%", (settings.master_volume * 100f32).round()),
+            Row::MusicVolume => format!("{}%", (settings.music_volume * 100f32).round()),
+            Row::SfxVolume => format!("{}%", (settings.sfx_volume * 100f32).round()),
+            Row::Fullscreen => on_off(settings.fullscreen),
+            Row::Resolution => format!("{}x{}", settings.resolution.0, settings.resolution.1),
+            Row::TextSpeed => match TEXT_SPEEDS
+                .iter()
+                .find(|(speed, _)| *speed == settings.text_speed)
+            {
+                Some((_, name)) => String::from(strings.get(name)),
+                None => format!("{}x", settings.text_speed),
+            },
+            Row::Colorblind => {
+                String::from(strings.get(&format!("colorblind.{}", settings.colorblind.get_name())))
+            }
+            Row::Subtitles => on_off(settings.subtitles),
+            // every language is shown by its own name
+            Row::Language => String::from(strings.get("language")),
+            Row::Key(action) if self.waiting_for_key == Some(action) => {
+                String::from(strings.get("settings.press_key"))
+            }
+            Row::Key(action) => {
+                let names: Vec<&str> = settings
+                    .get_keys(action)
+                    .into_iter()
+                    .filter_map(input::get_key_name)
+                    .collect();
+                if names.is_empty() {
+                    String::from(strings.get("settings.no_keys"))
+                } else {
+                    names.join(", ")
+                }
+            }
+            Row::ResetKeys => String::new(),
+        }
+    }
+
+    // wrap is set when changing by picking the row, which only goes forward
+    fn change(&mut self, row: Row, forward: bool, wrap: bool) {
+        let mut settings = self.settings.borrow_mut();
+        match row {
+            Row::MasterVolume => step_volume(&mut settings.master_volume, forward, wrap),
+            Row::MusicVolume => step_volume(&mut settings.music_volume, forward, wrap),
+            Row::SfxVolume => step_volume(&mut settings.sfx_volume, forward, wrap),
+            Row::Fullscreen => settings.fullscreen = !settings.fullscreen,
+            Row::Resolution => {
+                let idx = RESOLUTIONS
+                    .iter()
+                    .position(|resolution| *resolution == settings.resolution);
+                settings.resolution = match idx {
+                    Some(idx) => RESOLUTIONS[cycle(idx, RESOLUTIONS.len(), forward)],
+                    None => RESOLUTIONS[0],
+                };
+            }
+            Row::TextSpeed => {
+                let idx = TEXT_SPEEDS
+                    .iter()
+                    .position(|(speed, _)| *speed == settings.text_speed);
+                settings.text_speed = match idx {
+                    Some(idx) => TEXT_SPEEDS[cycle(idx, TEXT_SPEEDS.len(), forward)].0,
+                    None => 1f32,
+                };
+            }
+            Row::Colorblind => {
+                let idx = ColorblindMode::ALL
+                    .iter()
+                    .position(|mode| *mode == settings.colorblind)
+                    .unwrap_or(0);
+                settings.colorblind =
+                    ColorblindMode::ALL[cycle(idx, ColorblindMode::ALL.len(), forward)];
+            }
+            Row::Subtitles => settings.subtitles = !settings.subtitles,
+            Row::Language => {
+                let idx = self
+                    .languages
+                    .iter()
+                    .position(|(name, _, _)| *name == settings.language)
+                    .unwrap_or(0);
+                if let Some((name, _, _)) =
+                    self.languages
+                        .get(cycle(idx, self.languages.len(), forward))
+                {
+                    settings.language = name.clone();
+                }
+            }
+            // keys are only changed by picking them
+            Row::Key(action) => {
+                if wrap {
+                    self.waiting_for_key = Some(action);
+                }
+            }
+            Row::ResetKeys => {
+                if wrap {
+                    settings.key_bindings = input::get_default_keys();
+                }
+            }
+        }
+    }
+}
+
+impl Scene for SettingsScene {
+    fn update(&mut self, _ctx: &mut Context, _dt: f32) -> GameResult<()> {
+        Ok(())
+    }
+
+    fn draw(&mut self, ctx: &mut Context, _alpha: f32) -> GameResult<()> {
+        let title_text = self.get_text("menu.settings", 32f32);
+        let text_width = title_text.width(ctx) as f32 / 2f32;
+        graphics::draw(
+            ctx,
+            &title_text,
+            DrawParam::new().dest([400f32 - text_width, 20f32]),
+        )?;
+
+        for (idx, row) in self.rows.iter().enumerate() {
+            let y = LIST_Y + idx as f32 * ROW_HEIGHT;
+            if idx == self.cursor {
+                let cursor_mesh = Mesh::new_rectangle(
+                    ctx,
+                    DrawMode::stroke(2f32),
+                    Rect::new(LIST_X, y, ROW_WIDTH, ROW_HEIGHT - 2f32),
+                    Color::from_rgb(0xff, 0xff, 0xff),
+                )?;
+                graphics::draw(ctx, &cursor_mesh, DrawParam::new())?;
+            }
+            let label = self.get_text(row.get_label(), 20f32);
+            graphics::draw(
+                ctx,
+                &label,
+                DrawParam::new().dest([LIST_X + 10f32, y + 3f32]),
+            )?;
+
+            let value = self.get_language().1.text(&self.get_value(*row), 20f32);
+            let value_width = value.width(ctx) as f32;
+            graphics::draw(
+                ctx,
+                &value,
+                DrawParam::new()
+                    .dest([LIST_X + ROW_WIDTH - 10f32 - value_width, y + 3f32])
+                    .color(Color::from_rgb(0xa0, 0xa0, 0xff)),
+            )?;
+        }
+
+        let (strings, fonts) = self.get_language();
+        let help_text = fonts.wrapped(
+            strings.get("settings.help"),
+            16f32,
+            HELP_WIDTH,
+            Align::Center,
+        );
+        graphics::draw(
+            ctx,
+            &help_text,
+            DrawParam::new().dest([400f32 - HELP_WIDTH / 2f32, 496f32]),
+        )?;
+
+        let back_mesh = Mesh::new_rectangle(
+            ctx,
+            DrawMode::stroke(2f32),
+            Rect::new(
+                BACK_BUTTON[0],
+                BACK_BUTTON[1],
+                BACK_BUTTON[2],
+                BACK_BUTTON[3],
+            ),
+            Color::from_rgb(0xff, 0xff, 0xff),
+        )?;
+        graphics::draw(ctx, &back_mesh, DrawParam::new())?;
+        let back_text = self.get_text("menu.back", 26f32);
+        let text_width = back_text.width(ctx) as f32 / 2f32;
+        graphics::draw(
+            ctx,
+            &back_text,
+            DrawParam::new().dest([400f32 - text_width, BACK_BUTTON[1] + 6f32]),
+        )?;
+
+        Ok(())
+    }
+
+    fn finished(&self) -> bool {
+        self.finished
+    }
+
+    fn mouse_button_down_event(&mut self, _ctx: &mut Context, button: MouseButton, x: f32, y: f32) {
+        if button != MouseButton::Left || self.waiting_for_key.is_some() {
+            return;
+        }
+        if x > BACK_BUTTON[0]
+            && x < BACK_BUTTON[0] + BACK_BUTTON[2]
+            && y > BACK_BUTTON[1]
+            && y < BACK_BUTTON[1] + BACK_BUTTON[3]
+        {
+            self.finished = true;
+        } else if x > LIST_X && x < LIST_X + ROW_WIDTH && y > LIST_Y {
+            let idx = ((y - LIST_Y) / ROW_HEIGHT) as usize;
+            if idx < self.rows.len() {
+                self.cursor = idx;
+                self.change(self.rows[idx], true, true);
+            }
+        }
+    }
+
+    fn key_down_event(&mut self, _ctx: &mut Context, keycode: KeyCode) -> bool {
+        let action = match self.waiting_for_key {
+            Some(action) => action,
+            None => return false,
+        };
+        if keycode != KeyCode::Escape && input::get_key_name(keycode).is_some() {
+            self.settings.borrow_mut().bind_key(keycode, action);
+        }
+        self.waiting_for_key = None;
+        true
+    }
+
+    fn action_down_event(&mut self, _ctx: &mut Context, action: Action) {
+        if self.waiting_for_key.is_some() {
+            return;
+        }
+        match action {
+            Action::Up => {
+                if self.cursor > 0 {
+                    self.cursor -= 1;
+                }
+            }
+            Action::Down => {
+                if self.cursor + 1 < self.rows.len() {
+                    self.cursor += 1;
+                }
+            }
+            Action::Left => self.change(self.rows[self.cursor], false, false),
+            Action::Right => self.change(self.rows[self.cursor], true, false),
+            Action::Interact => self.change(self.rows[self.cursor], true, true),
+            Action::Back => self.finished = true,
+            Action::EnterDoor => (),
+        }
+    }
+}
@@ -0,0 +1,281 @@
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+use ggez::input::keyboard::KeyCode;
+use ggez::{filesystem, Context};
+
+use ld47_core::typewriter;
+use ld47_core::world::{SCREEN_HEIGHT, SCREEN_WIDTH};
+
+use crate::assets::DEFAULT_LANGUAGE;
+use crate::config;
+use crate::input::{self, Action};
+
+// in the user config directory, next to the enabled mods
+const SETTINGS_FILE: &str = "settings.txt";
+
+pub const RESOLUTIONS: [(f32, f32); 4] = [
+    (800f32, 600f32),
+    (1024f32, 768f32),
+    (1280f32, 960f32),
+    (1600f32, 1200f32),
+];
+// (speed, string id of its name)
+pub const TEXT_SPEEDS: [(f32, &str); 5] = [
+    (0.5f32, "text_speed.slow"),
+    (1f32, "text_speed.normal"),
+    (2f32, "text_speed.fast"),
+    (4f32, "text_speed.very_fast"),
+    (typewriter::INSTANT, "text_speed.instant"),
+];
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum ColorblindMode {
+    Off,
+    Deuteranopia,
+    Protanopia,
+    Tritanopia,
+    HighContrast,
+}
+
+impl ColorblindMode {
+    pub const ALL: [ColorblindMode; 5] = [
+        ColorblindMode::Off,
+        ColorblindMode::Deuteranopia,
+        ColorblindMode::Protanopia,
+        ColorblindMode::Tritanopia,
+        ColorblindMode::HighContrast,
+    ];
+
+    pub fn get_name(self) -> &'static str {
+        match self {
+            ColorblindMode::Off => "off",
+            ColorblindMode::Deuteranopia => "deuteranopia",
+            ColorblindMode::Protanopia => "protanopia",
+            ColorblindMode::Tritanopia => "tritanopia",
+            ColorblindMode::HighContrast => "high_contrast",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<ColorblindMode> {
+        ColorblindMode::ALL
+            .iter()
+            .copied()
+            .find(|mode| mode.get_name() == name)
+    }
+}
+
+// Everything that is picked in the settings menu and kept between runs
+#[derive(Clone, Debug)]
+pub struct Settings {
+    pub master_volume: f32,
+    pub music_volume: f32,
+    pub sfx_volume: f32,
+    pub fullscreen: bool,
+    pub resolution: (f32, f32),
+    pub text_speed: f32,
+    pub key_bindings: Vec<(KeyCode, Action)>,
+    pub colorblind: ColorblindMode,
+    pub subtitles: bool,
+    // the name of the strings in assets.manifest
+    pub language: String,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            master_volume: 1f32,
+            music_volume: 1f32,
+            sfx_volume: 1f32,
+            fullscreen: false,
+            resolution: (SCREEN_WIDTH, SCREEN_HEIGHT),
+            text_speed: 1f32,
+            key_bindings: input::get_default_keys(),
+            colorblind: ColorblindMode::Off,
+            subtitles: false,
+            language: String::from(DEFAULT_LANGUAGE),
+        }
+    }
+}
+
+fn parse_bool(value: &str) -> Option<bool> {
+    match value {
+        "on" => Some(true),
+        "off" => Some(false),
+        _ => None,
+    }
+}
+
+fn get_bool_name(value: bool) -> &'static str {
+    if value {
+        "on"
+    } else {
+        "off"
+    }
+}
+
+pub fn parse_text_speed(value: &str) -> Option<f32> {
+    if value == "instant" {
+        return Some(typewriter::INSTANT);
+    }
+    match value.parse::<f32>() {
+        Ok(speed) if speed > 0f32 && speed.is_finite() => Some(speed),
+        _ => None,
+    }
+}
+
+pub fn get_text_speed_name(speed: f32) -> String {
+    if speed == typewriter::INSTANT {
+        String::from("instant")
+    } else {
+        speed.to_string()
+    }
+}
+
+fn parse_volume(value: &str) -> Option<f32> {
+    let volume: f32 = value.parse().ok()?;
+    if (0f32..=1f32).contains(&volume) {
+        Some(volume)
+    } else {
+        None
+    }
+}
+
+fn get_settings_path(ctx: &Context) -> PathBuf {
+    filesystem::user_config_dir(ctx).join(SETTINGS_FILE)
+}
+
+impl Settings {
+    // a line that can't be read keeps the default for that setting
+    fn parse(contents: &str) -> (Settings, Vec<String>) {
+        let mut settings = Settings::default();
+        let mut key_bindings = Vec::new();
+        let mut warnings = Vec::new();
+        for (idx, line) in contents.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let words: Vec<&str> = line.split_whitespace().collect();
+            let valid = match (words[0], words.len()) {
+                ("master_volume", 2) => parse_volume(words[1])
+                    .map(|volume| settings.master_volume = volume)
+                    .is_some(),
+                ("music_volume", 2) => parse_volume(words[1])
+                    .map(|volume| settings.music_volume = volume)
+                    .is_some(),
+                ("sfx_volume", 2) => parse_volume(words[1])
+                    .map(|volume| settings.sfx_volume = volume)
+                    .is_some(),
+                ("fullscreen", 2) => parse_bool(words[1])
+                    .map(|fullscreen| settings.fullscreen = fullscreen)
+                    .is_some(),
+                ("resolution", 2) => config::parse_resolution(words[1])
+                    .map(|resolution| settings.resolution = resolution)
+                    .is_some(),
+                ("text_speed", 2) => parse_text_speed(words[1])
+                    .map(|speed| settings.text_speed = speed)
+                    .is_some(),
+                ("colorblind", 2) => ColorblindMode::from_name(words[1])
+                    .map(|mode| settings.colorblind = mode)
+                    .is_some(),
+                ("subtitles", 2) => parse_bool(words[1])
+                    .map(|subtitles| settings.subtitles = subtitles)
+                    .is_some(),
+                ("language", 2) => {
+                    settings.language = words[1].to_string();
+                    true
+                }
+                ("key", 3) => match (input::key_from_name(words[1]), Action::from_name(words[2])) {
+                    (Some(key), Some(action)) => {
+                        key_bindings.push((key, action));
+                        true
+                    }
+                    _ => false,
+                },
+                _ => false,
+            };
+            if !valid {
+                warnings.push(format!(
+                    "{} line {}: invalid setting \"{}\"",
+                    SETTINGS_FILE,
+                    idx + 1,
+                    line
+                ));
+            }
+        }
+        // without any bindings there would be no way to play
+        if !key_bindings.is_empty() {
+            settings.key_bindings = key_bindings;
+        }
+        (settings, warnings)
+    }
+
+    pub fn load(ctx: &Context) -> Settings {
+        let contents = match fs::read_to_string(get_settings_path(ctx)) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Settings::default(),
+            Err(e) => {
+                println!("WARNING: Failed to read the settings: {}", e);
+                return Settings::default();
+            }
+        };
+        let (settings, warnings) = Settings::parse(&contents);
+        for warning in warnings {
+            println!("WARNING: {}", warning);
+        }
+        settings
+    }
+
+    pub fn save(&self, ctx: &Context) -> io::Result<()> {
+        let path = get_settings_path(ctx);
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let mut contents = String::new();
+        contents.push_str(&format!("master_volume {}\n", self.master_volume));
+        contents.push_str(&format!("music_volume {}\n", self.music_volume));
+        contents.push_str(&format!("sfx_volume {}\n", self.sfx_volume));
+        contents.push_str(&format!("fullscreen {}\n", get_bool_name(self.fullscreen)));
+        contents.push_str(&format!(
+            "resolution {}x{}\n",
+            self.resolution.0, self.resolution.1
+        ));
+        contents.push_str(&format!(
+            "text_speed {}\n",
+            get_text_speed_name(self.text_speed)
+        ));
+        contents.push_str(&format!("colorblind {}\n", self.colorblind.get_name()));
+        contents.push_str(&format!("subtitles {}\n", get_bool_name(self.subtitles)));
+        contents.push_str(&format!("language {}\n", self.language));
+        for (key, action) in &self.key_bindings {
+            if let Some(name) = input::get_key_name(*key) {
+                contents.push_str(&format!("key {} {}\n", name, action.get_name()));
+            }
+        }
+        fs::write(path, contents)
+    }
+
+    pub fn get_music_volume(&self) -> f32 {
+        self.master_volume * self.music_volume
+    }
+
+    pub fn get_sfx_volume(&self) -> f32 {
+        self.master_volume * self.sfx_volume
+    }
+
+    pub fn get_keys(&self, action: Action) -> Vec<KeyCode> {
+        self.key_bindings
+            .iter()
+            .filter(|(_, bound)| *bound == action)
+            .map(|(key, _)| *key)
+            .collect()
+    }
+
+    // the key is taken away from whatever it did before
+    pub fn bind_key(&mut self, key: KeyCode, action: Action) {
+        self.key_bindings.retain(|(bound_key, _)| *bound_key != key);
+        self.key_bindings.push((key, action));
+    }
+}